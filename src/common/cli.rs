@@ -1,5 +1,50 @@
 use std::error::Error;
 
+use async_trait::async_trait;
+use clap::ValueEnum;
+
+use crate::common::outcome::Outcome;
+use crate::dns::core::dns_resolver::DnsResolver;
+
+#[async_trait(?Send)]
 pub trait CliCommand<T> {
-    fn execute(&self) -> Result<(), Box<dyn Error>>;
+    async fn execute(&self, dns_resolver: &dyn DnsResolver) -> Result<Outcome, Box<dyn Error>>;
+}
+
+/// How the result of a command is printed
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text
+    #[default]
+    Text,
+
+    /// A single JSON document
+    Json,
+
+    /// A SARIF log for code scanning
+    Sarif,
+
+    /// A JUnit XML report for test results
+    Junit,
+}
+
+/// The lowest severity that makes a command fail
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum FailOn {
+    /// Fail on warnings and errors
+    #[default]
+    Warning,
+
+    /// Fail on errors only, warnings exit successfully
+    Error,
+}
+
+impl FailOn {
+    /// The outcome with the findings below the threshold ignored
+    pub fn apply(&self, outcome: Outcome) -> Outcome {
+        match (self, outcome) {
+            (FailOn::Error, Outcome::Warnings) => Outcome::Ok,
+            (_, outcome) => outcome,
+        }
+    }
 }
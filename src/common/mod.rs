@@ -3,4 +3,6 @@
 //! This module contains all the shared code.
 
 pub mod cli;
+pub mod outcome;
 pub mod presenter;
+pub mod syntax_error;
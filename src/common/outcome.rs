@@ -0,0 +1,27 @@
+/// How a command ended, ordered from the best to the worst outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    /// Nothing to report
+    Ok,
+
+    /// Only warnings were found
+    Warnings,
+
+    /// At least one error was found
+    Errors,
+
+    /// A DNS query failed temporarily, the result is incomplete
+    DnsFailure,
+}
+
+impl Outcome {
+    /// The process exit code of the outcome
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Outcome::Ok => 0,
+            Outcome::Warnings => 1,
+            Outcome::Errors => 2,
+            Outcome::DnsFailure => 3,
+        }
+    }
+}
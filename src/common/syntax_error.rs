@@ -0,0 +1,79 @@
+//! Errors pointing at the offending part of a record, printed with miette
+
+pub type LabelSpan = miette::LabeledSpan;
+pub type Severity = miette::Severity;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub severity: Option<Severity>,
+    pub src: Option<String>,
+    pub src_labels: Option<Vec<LabelSpan>>,
+    pub help: Option<String>,
+    pub code: Option<String>,
+    pub code_url: Option<String>,
+}
+
+impl SyntaxError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            severity: None,
+            src: None,
+            src_labels: None,
+            help: None,
+            code: None,
+            code_url: None,
+        }
+    }
+
+    pub fn with_src(mut self, code: impl Into<String>) -> Self {
+        self.src = Some(code.into());
+        self
+    }
+
+    pub fn with_src_labels(mut self, labels: impl IntoIterator<Item = LabelSpan>) -> Self {
+        self.src_labels = Some(labels.into_iter().collect());
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_code_url(mut self, url: impl Into<String>) -> Self {
+        self.code_url = Some(url.into());
+        self
+    }
+}
+
+impl From<SyntaxError> for miette::Report {
+    fn from(err: SyntaxError) -> Self {
+        let mut diag = miette::MietteDiagnostic::new(err.message.to_string());
+        if let Some(help) = err.help {
+            diag = diag.with_help(help);
+        }
+        if let Some(src_labels) = err.src_labels {
+            diag = diag.with_labels(src_labels);
+        }
+        if let Some(severity) = err.severity {
+            diag = diag.with_severity(severity);
+        }
+        let mut report = miette::Report::from(diag);
+        if let Some(src) = err.src {
+            report = report.with_source_code(src)
+        }
+        report
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+
+use crate::dmarc::domain::{
+    Authorization, LabelSpan, ReportUri, Severity, SyntaxError, Tag, TagValue,
+};
+
+/// A domain must not publish more than one DMARC record (RFC 7489 section 6.6.3)
+pub fn check_single_dmarc_record(records: &[String]) -> Result<(), Box<SyntaxError>> {
+    if records.len() <= 1 {
+        return Ok(());
+    }
+
+    let mut offset = 0;
+    let labels = records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let span = offset..offset + record.len();
+            offset += record.len() + 1;
+            LabelSpan::at(span, format!("DMARC record {}", index + 1))
+        })
+        .collect::<Vec<_>>();
+
+    Err(Box::new(
+        SyntaxError::new(format!(
+            "Multiple DMARC records found ({}), so no DMARC policy is applied",
+            records.len()
+        ))
+        .with_code("dmarc::multiple-records")
+        .with_src(records.join("\n"))
+        .with_src_labels(labels)
+        .with_help("Merge the DMARC records into a single TXT record."),
+    ))
+}
+
+/// The version tag must be the first tag (RFC 7489 section 6.3)
+pub fn check_version(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    match tags.first() {
+        Some(tag) if tag.name == "v" => Ok(()),
+        first_tag => {
+            let span = first_tag
+                .map(|tag| tag.span.clone())
+                .unwrap_or(0..raw_rdata.len());
+            Err(Box::new(
+                SyntaxError::new("Version must be the first tag")
+                    .with_code("dmarc::invalid-version")
+                    .with_src(raw_rdata)
+                    .with_src_labels(vec![LabelSpan::at(span, "Version is missing")])
+                    .with_help("Add 'v=DMARC1;' to the beginning of the DMARC record."),
+            ))
+        }
+    }
+}
+
+/// Every tag must follow the DMARC grammar (RFC 7489 section 6.4)
+pub fn check_syntax(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let errors = tags
+        .iter()
+        .filter_map(|tag| match &tag.value {
+            TagValue::Invalid(err) => Some(err),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    match errors.as_slice() {
+        [] => Ok(()),
+        [err] => Err(Box::new(
+            (***err).clone().with_code("dmarc::invalid-syntax"),
+        )),
+        [first_err, ..] => Err(Box::new(
+            SyntaxError::new("DMARC record contains invalid tags")
+                .with_code("dmarc::invalid-syntax")
+                .with_src(raw_rdata)
+                .with_src_labels(
+                    errors
+                        .iter()
+                        .flat_map(|err| err.src_labels.iter().flatten().cloned()),
+                )
+                .with_help(first_err.help.to_owned().unwrap_or_default()),
+        )),
+    }
+}
+
+/// The policy tag is required (RFC 7489 section 6.3)
+pub fn check_policy(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    if tags.iter().any(|tag| tag.name == "p") {
+        return Ok(());
+    }
+
+    let span = tags
+        .first()
+        .map(|tag| tag.span.clone())
+        .unwrap_or(0..raw_rdata.len());
+    Err(Box::new(
+        SyntaxError::new("Policy is missing")
+            .with_code("dmarc::missing-policy")
+            .with_src(raw_rdata)
+            .with_src_labels(vec![LabelSpan::at(span, "'p' must follow the version")])
+            .with_help("Add 'p=none;' after the version to monitor without affecting delivery."),
+    ))
+}
+
+/// A tag must not be given more than once (RFC 6376 section 3.2)
+pub fn check_duplicate_tags(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let mut names = HashSet::new();
+    let duplicates = tags
+        .iter()
+        .filter(|tag| !names.insert(tag.name.as_str()))
+        .map(|tag| {
+            LabelSpan::at(
+                tag.span.clone(),
+                format!("'{}' is already defined", tag.name),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("DMARC record contains duplicate tags")
+            .with_code("dmarc::duplicate-tag")
+            .with_src(raw_rdata)
+            .with_src_labels(duplicates)
+            .with_help("Remove the duplicate tags."),
+    ))
+}
+
+/// Unknown tags are ignored by receivers (RFC 7489 section 6.3)
+pub fn check_unknown_tags(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let unknown_tags = tags
+        .iter()
+        .filter(|tag| matches!(tag.value, TagValue::Unknown(_)))
+        .collect::<Vec<_>>();
+
+    if unknown_tags.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("DMARC record contains one or more unknown tags")
+            .with_code("dmarc::unknown-tag")
+            .with_severity(Severity::Warning)
+            .with_src(raw_rdata)
+            .with_src_labels(unknown_tags.iter().map(|tag| {
+                LabelSpan::at(
+                    tag.span.clone(),
+                    format!("'{}' is an unknown tag", tag.name),
+                )
+            }))
+            .with_help("Remove the unknown tags, receivers ignore them."),
+    ))
+}
+
+/// The maximum size of a report URI must be a number with an optional unit (RFC 7489 section 6.4)
+pub fn check_report_size(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let invalid_sizes = report_uris(tags)
+        .filter_map(|uri| {
+            let max_size = uri.max_size.as_ref()?;
+            if uri.max_size_bytes().is_some() {
+                return None;
+            }
+            Some(LabelSpan::at(
+                uri.span.end - max_size.len()..uri.span.end,
+                format!("'{}' is not a size", max_size),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    if invalid_sizes.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("Invalid maximum report size")
+            .with_code("dmarc::invalid-report-size")
+            .with_src(raw_rdata)
+            .with_src_labels(invalid_sizes)
+            .with_help("Use a number with an optional unit 'k', 'm', 'g' or 't' (e.g. 10m)."),
+    ))
+}
+
+/// Report destinations outside the domain must accept its reports (RFC 7489 section 7.1)
+pub fn check_report_authorization(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let unauthorized = report_uris(tags)
+        .filter_map(|uri| match &uri.authorization {
+            Some(Authorization::Unauthorized { name }) => Some(LabelSpan::at(
+                uri.span.clone(),
+                format!("No 'v=DMARC1' record at '{}'", name),
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if unauthorized.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("Report destinations do not accept the reports of the domain")
+            .with_code("dmarc::unauthorized-report-destination")
+            .with_severity(Severity::Warning)
+            .with_src(raw_rdata)
+            .with_src_labels(unauthorized)
+            .with_help(
+                "Publish a 'v=DMARC1' TXT record at '<domain>._report._dmarc.<destination>' \
+                 on the destination, or remove the destination.",
+            ),
+    ))
+}
+
+/// DNS queries for the authorization of report destinations must be answered
+pub fn check_report_lookup_errors(tags: &[Tag], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let errors = report_uris(tags)
+        .filter_map(|uri| match &uri.authorization {
+            Some(Authorization::LookupFailed(err)) => {
+                Some(LabelSpan::at(uri.span.clone(), err.to_string()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("DNS lookup failed")
+            .with_code("dmarc::lookup-failed")
+            .with_src(raw_rdata)
+            .with_src_labels(errors)
+            .with_help("Check that the name servers of the report destinations answer the query."),
+    ))
+}
+
+fn report_uris(tags: &[Tag]) -> impl Iterator<Item = &ReportUri> {
+    tags.iter().flat_map(|tag| match &tag.value {
+        TagValue::AggregateReportUris(uris) | TagValue::FailureReportUris(uris) => uris.as_slice(),
+        _ => &[],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::dmarc::domain::DmarcRecord;
+
+    #[test]
+    fn it_should_accept_valid_record() {
+        let record = DmarcRecord::parse("v=DMARC1; p=reject; rua=mailto:dmarc@example.com");
+
+        assert!(check_version(&record.tags, &record.raw_rdata).is_ok());
+        assert!(check_syntax(&record.tags, &record.raw_rdata).is_ok());
+        assert!(check_policy(&record.tags, &record.raw_rdata).is_ok());
+        assert!(check_duplicate_tags(&record.tags, &record.raw_rdata).is_ok());
+        assert!(check_unknown_tags(&record.tags, &record.raw_rdata).is_ok());
+    }
+
+    #[test]
+    fn it_should_require_version_first_and_policy() {
+        let record = DmarcRecord::parse("sp=none; v=DMARC1");
+
+        assert!(check_version(&record.tags, &record.raw_rdata).is_err());
+        assert!(check_policy(&record.tags, &record.raw_rdata).is_err());
+    }
+
+    #[test]
+    fn it_should_point_at_duplicate_tag() {
+        let record = DmarcRecord::parse("v=DMARC1; p=none; p=reject");
+
+        let err = check_duplicate_tags(&record.tags, &record.raw_rdata).unwrap_err();
+
+        let label = &err.src_labels.unwrap()[0];
+        assert_eq!((label.offset(), label.len()), (18, 8));
+    }
+
+    #[test]
+    fn it_should_point_at_invalid_report_size() {
+        let record = DmarcRecord::parse(
+            "v=DMARC1; p=none; rua=mailto:a@example.com!10m,mailto:b@example.com!10x",
+        );
+
+        let err = check_report_size(&record.tags, &record.raw_rdata).unwrap_err();
+
+        let labels = err.src_labels.unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!((labels[0].offset(), labels[0].len()), (68, 3));
+    }
+
+    #[test]
+    fn it_should_warn_about_unauthorized_report_destination() {
+        let mut record = DmarcRecord::parse("v=DMARC1; p=none; rua=mailto:a@example.net");
+        if let TagValue::AggregateReportUris(uris) = &mut record.tags[2].value {
+            uris[0].authorization = Some(Authorization::Unauthorized {
+                name: "example.com._report._dmarc.example.net".to_owned(),
+            });
+        }
+
+        let err = check_report_authorization(&record.tags, &record.raw_rdata).unwrap_err();
+
+        assert_eq!(err.severity, Some(Severity::Warning));
+        let label = &err.src_labels.unwrap()[0];
+        assert_eq!((label.offset(), label.len()), (22, 20));
+    }
+}
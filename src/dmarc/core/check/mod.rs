@@ -0,0 +1,9 @@
+mod checks;
+mod presenter;
+mod use_case;
+
+// TODO: we should export a Factorys instead of a concrete implementations
+pub use self::presenter::{SummaryDmarcTerminalPresenter, SummaryDmarcWithDetailTerminalPresenter};
+pub use self::use_case::{
+    DmarcSummary, SummaryDmarcQuery, SummaryDmarcUseCase, SummaryDmarcUseCaseImpl,
+};
@@ -0,0 +1,139 @@
+use crate::common::presenter::Presenter;
+use crate::dmarc::core::check::use_case::DmarcSummary;
+use crate::dmarc::domain::{Authorization, DmarcError, ReportUri, TagValue};
+
+#[derive(Default)]
+pub struct SummaryDmarcTerminalPresenter {}
+
+impl SummaryDmarcTerminalPresenter {
+    pub fn new() -> Self {
+        SummaryDmarcTerminalPresenter::default()
+    }
+}
+
+impl Presenter<DmarcSummary, DmarcError> for SummaryDmarcTerminalPresenter {
+    fn success(&mut self, data: &DmarcSummary) {
+        if !data.diagnostics.is_empty() {
+            data.diagnostics.iter().for_each(print_dmarc_error);
+            return;
+        }
+
+        print_record_location(data);
+        println!("Raw Record: '{}'", data.record.raw_rdata);
+        println!("DMARC looks good");
+    }
+    fn error(&mut self, error: &DmarcError) {
+        print_dmarc_error(error);
+    }
+}
+
+#[derive(Default)]
+pub struct SummaryDmarcWithDetailTerminalPresenter {}
+
+impl SummaryDmarcWithDetailTerminalPresenter {
+    pub fn new() -> Self {
+        SummaryDmarcWithDetailTerminalPresenter::default()
+    }
+}
+
+impl Presenter<DmarcSummary, DmarcError> for SummaryDmarcWithDetailTerminalPresenter {
+    fn success(&mut self, data: &DmarcSummary) {
+        if !data.diagnostics.is_empty() {
+            data.diagnostics.iter().for_each(print_dmarc_error);
+            return;
+        }
+
+        print_record_location(data);
+        println!("Raw Record: '{}'", data.record.raw_rdata);
+        data.record.tags.iter().for_each(|tag| {
+            let description = match &tag.value {
+                TagValue::Version => "Version".to_owned(),
+                TagValue::Policy(policy) => format!("Policy: {}", policy),
+                TagValue::SubdomainPolicy(policy) => format!("Subdomain policy: {}", policy),
+                TagValue::NonExistentSubdomainPolicy(policy) => {
+                    format!("Non-existent subdomain policy: {}", policy)
+                }
+                TagValue::Percentage(percentage) => {
+                    format!("Applied to {}% of failing mail", percentage)
+                }
+                TagValue::AggregateReportUris(uris) => {
+                    format!("Aggregate reports to: {}", format_uris(uris))
+                }
+                TagValue::FailureReportUris(uris) => {
+                    format!("Failure reports to: {}", format_uris(uris))
+                }
+                TagValue::DkimAlignment(alignment) => format!("DKIM alignment: {}", alignment),
+                TagValue::SpfAlignment(alignment) => format!("SPF alignment: {}", alignment),
+                TagValue::FailureOptions(options) => format!(
+                    "Failure reporting options: {}",
+                    options
+                        .iter()
+                        .map(|option| option.to_string())
+                        .collect::<Vec<_>>()
+                        .join(":")
+                ),
+                TagValue::ReportFormats(formats) => {
+                    format!("Failure report formats: {}", formats.join(":"))
+                }
+                TagValue::ReportInterval(interval) => {
+                    format!("Aggregate report interval: {} seconds", interval)
+                }
+                TagValue::Unknown(_) => "Unknown tag".to_owned(),
+                TagValue::Invalid(err) => format!("Invalid tag: {}", err.message),
+            };
+            println!(
+                "- {}\n\t {}",
+                &data.record.raw_rdata[tag.span.clone()],
+                description
+            );
+        });
+    }
+    fn error(&mut self, error: &DmarcError) {
+        print_dmarc_error(error);
+    }
+}
+
+fn print_record_location(data: &DmarcSummary) {
+    if !data
+        .record_domain_name
+        .eq_ignore_ascii_case(&data.domain_name)
+    {
+        println!(
+            "No record for '{}', using the record of the organizational domain '{}'",
+            data.domain_name, data.record_domain_name
+        );
+    }
+}
+
+fn format_uris(uris: &[ReportUri]) -> String {
+    uris.iter()
+        .map(|uri| match &uri.authorization {
+            None => uri.to_string(),
+            Some(Authorization::Authorized) => format!("{} (authorized)", uri),
+            Some(Authorization::Unauthorized { .. }) => format!("{} (not authorized)", uri),
+            Some(Authorization::LookupFailed(err)) => format!("{} ({})", uri, err),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_dmarc_error(error: &DmarcError) {
+    match error {
+        DmarcError::NoDmarcRecordFound(message) => {
+            eprintln!("Error: {}", message);
+        }
+        DmarcError::MultipleDmarcRecordsFound { domain_name, .. } => {
+            eprintln!("Error: Multiple DMARC records found for '{}'", domain_name);
+        }
+        DmarcError::TempError(err) => {
+            eprintln!("Temporary DNS error: {}", err);
+        }
+        DmarcError::PermError(err) => {
+            eprintln!("DNS error: {}", err);
+        }
+        DmarcError::SyntaxError(err) => {
+            let report: miette::Report = (*err).clone().into();
+            eprintln!("{:?}", report);
+        }
+    }
+}
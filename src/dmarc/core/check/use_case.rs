@@ -0,0 +1,245 @@
+use async_trait::async_trait;
+
+use crate::common::outcome::Outcome;
+use crate::common::presenter::Presenter;
+use crate::dmarc::core::check::checks::{
+    check_duplicate_tags, check_policy, check_report_authorization, check_report_lookup_errors,
+    check_report_size, check_single_dmarc_record, check_syntax, check_unknown_tags, check_version,
+};
+use crate::dmarc::core::resolver::use_case::{ResolveDmarcQuery, ResolveDmarcUseCase};
+use crate::dmarc::core::ResolveDmarcUseCaseImpl;
+use crate::dmarc::domain::{Authorization, DmarcError, DmarcRecord, Severity, TagValue};
+use crate::dns::core::dns_resolver::DnsResolver;
+
+#[async_trait(?Send)]
+pub trait SummaryDmarcUseCase {
+    /// Summary the DMARC record of a domain name.
+    ///
+    /// The presenter gets the summary with its diagnostics if a record was resolved,
+    /// otherwise the error that prevented resolving it.
+    /// Returns the outcome of the checks.
+    async fn execute(
+        &mut self,
+        query: &SummaryDmarcQuery,
+        presenter: Box<dyn Presenter<DmarcSummary, DmarcError>>,
+    ) -> Outcome;
+}
+
+pub struct DmarcSummary {
+    /// The checked domain name
+    pub domain_name: String,
+
+    /// The domain name the record was found at, the organizational domain on a fallback
+    pub record_domain_name: String,
+
+    pub record: DmarcRecord,
+
+    /// The errors and warnings of the checks, the record is valid without errors
+    pub diagnostics: Vec<DmarcError>,
+}
+
+pub struct SummaryDmarcQuery {
+    pub domain_name: String,
+    pub record: Option<String>,
+}
+
+pub struct SummaryDmarcUseCaseImpl<'a> {
+    dmarc_resolver: Box<dyn ResolveDmarcUseCase + 'a>,
+}
+
+impl<'a> SummaryDmarcUseCaseImpl<'a> {
+    pub fn new(dns_resolver: &'a dyn DnsResolver) -> Self {
+        SummaryDmarcUseCaseImpl {
+            dmarc_resolver: Box::new(ResolveDmarcUseCaseImpl::new(dns_resolver)),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a> SummaryDmarcUseCase for SummaryDmarcUseCaseImpl<'a> {
+    async fn execute(
+        &mut self,
+        query: &SummaryDmarcQuery,
+        mut presenter: Box<dyn Presenter<DmarcSummary, DmarcError>>,
+    ) -> Outcome {
+        let resolved = self
+            .dmarc_resolver
+            .resolve(&ResolveDmarcQuery {
+                domain_name: query.domain_name.to_owned(),
+                record: query.record.to_owned(),
+            })
+            .await;
+
+        let resolved = match resolved {
+            Ok(resolved) => resolved,
+            Err(err) => {
+                match *err {
+                    DmarcError::MultipleDmarcRecordsFound { records, .. } => {
+                        if let Err(err) = check_single_dmarc_record(&records) {
+                            presenter.error(&(*err).into());
+                        }
+                    }
+                    DmarcError::TempError(err) => {
+                        presenter.error(&DmarcError::TempError(err));
+                        return Outcome::DnsFailure;
+                    }
+                    err => presenter.error(&err),
+                }
+                return Outcome::Errors;
+            }
+        };
+
+        // checks
+        let record = resolved.record;
+        let mut check_errors: Vec<DmarcError> = vec![];
+        if let Err(err) = check_version(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_syntax(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_policy(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_duplicate_tags(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_unknown_tags(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_report_size(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_report_authorization(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_report_lookup_errors(&record.tags, &record.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+
+        let outcome = outcome(&record, &check_errors);
+        presenter.success(&DmarcSummary {
+            domain_name: query.domain_name.to_owned(),
+            record_domain_name: resolved.domain_name,
+            record,
+            diagnostics: check_errors,
+        });
+
+        outcome
+    }
+}
+
+/// The worst outcome of the diagnostics, a temporarily failed lookup of a report
+/// destination makes the checks incomplete
+fn outcome(record: &DmarcRecord, diagnostics: &[DmarcError]) -> Outcome {
+    let has_temporary_lookup_error = record.tags.iter().any(|tag| match &tag.value {
+        TagValue::AggregateReportUris(uris) | TagValue::FailureReportUris(uris) => {
+            uris.iter().any(|uri| {
+                matches!(&uri.authorization, Some(Authorization::LookupFailed(err)) if err.is_temporary())
+            })
+        }
+        _ => false,
+    });
+    if has_temporary_lookup_error {
+        return Outcome::DnsFailure;
+    }
+
+    diagnostics
+        .iter()
+        .map(|diagnostic| match diagnostic.severity() {
+            Severity::Error => Outcome::Errors,
+            Severity::Warning => Outcome::Warnings,
+            Severity::Advice => Outcome::Ok,
+        })
+        .max()
+        .unwrap_or(Outcome::Ok)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dns::core::dns_resolver::MockDnsResolver;
+
+    struct ErrorPresenter {
+        errors: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Presenter<DmarcSummary, DmarcError> for ErrorPresenter {
+        fn success(&mut self, data: &DmarcSummary) {
+            data.diagnostics.iter().for_each(|err| self.error(err));
+        }
+
+        fn error(&mut self, error: &DmarcError) {
+            let message = match error {
+                DmarcError::NoDmarcRecordFound(message) => message.to_owned(),
+                DmarcError::MultipleDmarcRecordsFound { domain_name, .. } => domain_name.to_owned(),
+                DmarcError::TempError(err) | DmarcError::PermError(err) => err.to_string(),
+                DmarcError::SyntaxError(err) => err.message.to_owned(),
+            };
+            self.errors.borrow_mut().push(message);
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_report_invalid_record() {
+        // Arrange
+        let dns_resolver = MockDnsResolver::new();
+        let errors = Rc::new(RefCell::new(vec![]));
+        let presenter = Box::new(ErrorPresenter {
+            errors: errors.clone(),
+        });
+        let mut summary_dmarc_use_case = SummaryDmarcUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let outcome = summary_dmarc_use_case
+            .execute(
+                &SummaryDmarcQuery {
+                    domain_name: "example.com".to_owned(),
+                    record: Some("v=DMARC1; pct=200; x=y".to_owned()),
+                },
+                presenter,
+            )
+            .await;
+
+        // Assert
+        assert_eq!(outcome, Outcome::Errors);
+        assert_eq!(
+            *errors.borrow(),
+            vec![
+                "Invalid percentage",
+                "Policy is missing",
+                "DMARC record contains one or more unknown tags"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_accept_valid_record() {
+        // Arrange
+        let dns_resolver = MockDnsResolver::new();
+        let errors = Rc::new(RefCell::new(vec![]));
+        let presenter = Box::new(ErrorPresenter {
+            errors: errors.clone(),
+        });
+        let mut summary_dmarc_use_case = SummaryDmarcUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let outcome = summary_dmarc_use_case
+            .execute(
+                &SummaryDmarcQuery {
+                    domain_name: "example.com".to_owned(),
+                    record: Some("v=DMARC1; p=quarantine; adkim=s".to_owned()),
+                },
+                presenter,
+            )
+            .await;
+
+        // Assert
+        assert_eq!(outcome, Outcome::Ok);
+        assert!(errors.borrow().is_empty());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod check;
+mod resolver;
+
+pub(in crate::dmarc) use resolver::use_case::ResolveDmarcUseCaseImpl;
@@ -0,0 +1,303 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+
+use crate::dmarc::domain::{Authorization, DmarcError, DmarcRecord, ReportUri, TagValue};
+use crate::dns::core::dns_resolver::{DnsResolver, TxtRecordQuery};
+use crate::dns::domain::organizational_domain;
+
+#[async_trait]
+pub trait ResolveDmarcUseCase {
+    /// Fetch (unless given) and parse the DMARC record of a domain name,
+    /// falling back to the record of the organizational domain (RFC 7489 section 6.6.3).
+    /// Report destinations outside the domain are resolved to whether they accept its reports.
+    async fn resolve(
+        &self,
+        query: &ResolveDmarcQuery,
+    ) -> Result<ResolvedDmarcRecord, Box<DmarcError>>;
+}
+
+pub struct ResolveDmarcQuery {
+    /// The domain name to query
+    pub domain_name: String,
+
+    /// The DMARC record to parse. If not provided, the record will be fetched from DNS.
+    pub record: Option<String>,
+}
+
+pub struct ResolvedDmarcRecord {
+    /// The domain name the record was found at, the organizational domain on a fallback
+    pub domain_name: String,
+    pub record: DmarcRecord,
+}
+
+pub struct ResolveDmarcUseCaseImpl<'a> {
+    dns_resolver: &'a dyn DnsResolver,
+}
+
+impl<'a> ResolveDmarcUseCaseImpl<'a> {
+    pub fn new(dns_resolver: &'a dyn DnsResolver) -> Self {
+        ResolveDmarcUseCaseImpl { dns_resolver }
+    }
+
+    /// The DMARC records published at `_dmarc.<domain name>`, other TXT records are discarded
+    async fn query_records(&self, domain_name: &str) -> Result<Vec<String>, Box<DmarcError>> {
+        let result = self
+            .dns_resolver
+            .query_txt(&TxtRecordQuery {
+                domain_name: format!("_dmarc.{}", domain_name.trim_end_matches('.')),
+            })
+            .await;
+
+        match result {
+            Ok(txt_record) => Ok(txt_record
+                .texts()
+                .into_iter()
+                .filter(|text| DmarcRecord::is_dmarc_record(text))
+                .collect()),
+            Err(err) if err.is_void() => Ok(vec![]),
+            Err(err) => Err(Box::new(err.into())),
+        }
+    }
+
+    /// Resolve the authorization of the report destinations outside the domain of the record
+    async fn resolve_report_uris(&self, domain_name: &str, mut record: DmarcRecord) -> DmarcRecord {
+        let own_organizational_domain = registered_domain(domain_name);
+        let uris = record
+            .tags
+            .iter_mut()
+            .flat_map(|tag| match &mut tag.value {
+                TagValue::AggregateReportUris(uris) | TagValue::FailureReportUris(uris) => {
+                    uris.iter_mut().collect()
+                }
+                _ => vec![],
+            })
+            .filter(|uri| {
+                uri.mailto_domain().is_some_and(|destination| {
+                    registered_domain(&destination) != own_organizational_domain
+                })
+            });
+
+        join_all(uris.map(|uri| self.resolve_authorization_mut(domain_name, uri))).await;
+
+        record
+    }
+
+    /// Query `<domain>._report._dmarc.<destination>` for a "v=DMARC1" record (RFC 7489 section 7.1)
+    async fn resolve_authorization_mut(&self, domain_name: &str, uri: &mut ReportUri) {
+        let Some(destination) = uri.mailto_domain() else {
+            return;
+        };
+        let name = format!(
+            "{}._report._dmarc.{}",
+            domain_name.trim_end_matches('.').to_ascii_lowercase(),
+            destination
+        );
+
+        let result = self
+            .dns_resolver
+            .query_txt(&TxtRecordQuery {
+                domain_name: name.to_owned(),
+            })
+            .await;
+        uri.authorization = Some(match result {
+            Ok(txt_record)
+                if txt_record
+                    .texts()
+                    .iter()
+                    .any(|text| DmarcRecord::is_dmarc_record(text)) =>
+            {
+                Authorization::Authorized
+            }
+            Ok(_) => Authorization::Unauthorized { name },
+            Err(err) if err.is_void() => Authorization::Unauthorized { name },
+            Err(err) => Authorization::LookupFailed(err),
+        });
+    }
+}
+
+#[async_trait]
+impl<'a> ResolveDmarcUseCase for ResolveDmarcUseCaseImpl<'a> {
+    async fn resolve(
+        &self,
+        query: &ResolveDmarcQuery,
+    ) -> Result<ResolvedDmarcRecord, Box<DmarcError>> {
+        if let Some(record) = &query.record {
+            return Ok(ResolvedDmarcRecord {
+                domain_name: query.domain_name.to_owned(),
+                record: self
+                    .resolve_report_uris(&query.domain_name, DmarcRecord::parse(record))
+                    .await,
+            });
+        }
+
+        let mut domain_name = query.domain_name.to_owned();
+        let mut records = self.query_records(&domain_name).await?;
+        if records.is_empty() {
+            let organizational_domain = registered_domain(&query.domain_name);
+            if !organizational_domain.eq_ignore_ascii_case(domain_name.trim_end_matches('.')) {
+                log::debug!(
+                    "No DMARC record for '{}', falling back to '{}'",
+                    domain_name,
+                    organizational_domain
+                );
+                records = self.query_records(&organizational_domain).await?;
+                domain_name = organizational_domain;
+            }
+        }
+
+        // Policy discovery ends without a single record (RFC 7489 section 6.6.3)
+        match records.as_slice() {
+            [] => Err(Box::new(DmarcError::NoDmarcRecordFound(format!(
+                "No DMARC record found for '{}'",
+                query.domain_name
+            )))),
+            [record] => Ok(ResolvedDmarcRecord {
+                record: self
+                    .resolve_report_uris(&domain_name, DmarcRecord::parse(record))
+                    .await,
+                domain_name,
+            }),
+            _ => Err(Box::new(DmarcError::MultipleDmarcRecordsFound {
+                domain_name,
+                records,
+            })),
+        }
+    }
+}
+
+/// The organizational domain of a domain name, the domain name itself if it is a public suffix
+fn registered_domain(domain_name: &str) -> String {
+    organizational_domain(domain_name)
+        .unwrap_or_else(|| domain_name.trim_end_matches('.').to_lowercase())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::dns::core::dns_resolver::MockDnsResolver;
+    use crate::dns::domain::{DnsError, TxtRecord};
+
+    #[tokio::test]
+    async fn it_should_fall_back_to_organizational_domain() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .returning(|query| match query.domain_name.as_str() {
+                "_dmarc.example.com" => Ok(TxtRecord {
+                    records: vec![
+                        vec!["v=spf1 -all".to_owned()],
+                        vec!["v=DMARC1; p=reject".to_owned()],
+                    ],
+                    ttl: 3600,
+                }),
+                _ => Err(DnsError::NxDomain {
+                    name: query.domain_name.to_owned(),
+                    negative_ttl: None,
+                }),
+            });
+        let use_case = ResolveDmarcUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let resolved = use_case
+            .resolve(&ResolveDmarcQuery {
+                domain_name: "mail.example.com".to_owned(),
+                record: None,
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(resolved.domain_name, "example.com");
+        assert_eq!(resolved.record.raw_rdata, "v=DMARC1; p=reject");
+    }
+
+    #[tokio::test]
+    async fn it_should_reject_multiple_records() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().returning(|_| {
+            Ok(TxtRecord {
+                records: vec![
+                    vec!["v=DMARC1; p=none".to_owned()],
+                    vec!["v=DMARC1; p=reject".to_owned()],
+                ],
+                ttl: 3600,
+            })
+        });
+        let use_case = ResolveDmarcUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let result = use_case
+            .resolve(&ResolveDmarcQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+            })
+            .await;
+
+        // Assert
+        assert!(matches!(
+            result.err().as_deref(),
+            Some(DmarcError::MultipleDmarcRecordsFound { records, .. }) if records.len() == 2
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_resolve_external_report_destinations() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .returning(|query| match query.domain_name.as_str() {
+                "example.com._report._dmarc.reports.example.net" => Ok(TxtRecord {
+                    records: vec![vec!["v=DMARC1".to_owned()]],
+                    ttl: 3600,
+                }),
+                "example.com._report._dmarc.example.org" => {
+                    Err(DnsError::ServFail(query.domain_name.to_owned()))
+                }
+                _ => Err(DnsError::NxDomain {
+                    name: query.domain_name.to_owned(),
+                    negative_ttl: None,
+                }),
+            });
+        let use_case = ResolveDmarcUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let resolved = use_case
+            .resolve(&ResolveDmarcQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some(
+                    "v=DMARC1; p=none; rua=mailto:a@mail.example.com,mailto:b@reports.example.net; \
+                     ruf=mailto:c@example.org,mailto:d@example.info"
+                        .to_owned(),
+                ),
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        let authorizations = [
+            resolved.record.aggregate_report_uris(),
+            resolved.record.failure_report_uris(),
+        ]
+        .concat()
+        .into_iter()
+        .map(|uri| uri.authorization)
+        .collect::<Vec<_>>();
+        assert_eq!(
+            authorizations,
+            vec![
+                None,
+                Some(Authorization::Authorized),
+                Some(Authorization::LookupFailed(DnsError::ServFail(
+                    "example.com._report._dmarc.example.org".to_owned()
+                ))),
+                Some(Authorization::Unauthorized {
+                    name: "example.com._report._dmarc.example.info".to_owned()
+                }),
+            ]
+        );
+    }
+}
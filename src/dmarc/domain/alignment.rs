@@ -0,0 +1,34 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// How closely the authenticated domain must match the From domain (RFC 7489 section 3.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// The organizational domains must match
+    #[default]
+    Relaxed,
+    /// The domain names must be identical
+    Strict,
+}
+
+impl FromStr for Alignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "r" => Ok(Alignment::Relaxed),
+            "s" => Ok(Alignment::Strict),
+            _ => Err(format!("Unknown alignment mode: {}", s)),
+        }
+    }
+}
+
+impl Display for Alignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let alignment = match self {
+            Alignment::Relaxed => "relaxed",
+            Alignment::Strict => "strict",
+        };
+        write!(f, "{}", alignment)
+    }
+}
@@ -0,0 +1,42 @@
+use crate::common::syntax_error::{Severity, SyntaxError};
+use crate::dns::domain::DnsError;
+
+#[derive(Debug)]
+pub enum DmarcError {
+    NoDmarcRecordFound(String),
+    MultipleDmarcRecordsFound {
+        domain_name: String,
+        records: Vec<String>,
+    },
+    /// A DNS query failed, retrying later may succeed
+    TempError(DnsError),
+    /// A DNS query failed for a reason that will not resolve itself
+    PermError(DnsError),
+    SyntaxError(SyntaxError),
+}
+
+impl DmarcError {
+    /// Errors other than syntax errors always make the record invalid
+    pub fn severity(&self) -> Severity {
+        match self {
+            DmarcError::SyntaxError(err) => err.severity.unwrap_or(Severity::Error),
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl From<DnsError> for DmarcError {
+    fn from(err: DnsError) -> Self {
+        if err.is_temporary() {
+            Self::TempError(err)
+        } else {
+            Self::PermError(err)
+        }
+    }
+}
+
+impl From<SyntaxError> for DmarcError {
+    fn from(err: SyntaxError) -> Self {
+        Self::SyntaxError(err)
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// When a failure report is requested (RFC 7489 section 6.3 "fo")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOption {
+    /// The option "0" requests a report if all mechanisms fail to produce an aligned pass
+    All,
+    /// The option "1" requests a report if any mechanism produced something other than an aligned pass
+    Any,
+    /// The option "d" requests a report if the DKIM signature failed
+    Dkim,
+    /// The option "s" requests a report if SPF failed
+    Spf,
+}
+
+impl FromStr for FailureOption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "0" => Ok(FailureOption::All),
+            "1" => Ok(FailureOption::Any),
+            "d" => Ok(FailureOption::Dkim),
+            "s" => Ok(FailureOption::Spf),
+            _ => Err(format!("Unknown failure reporting option: {}", s)),
+        }
+    }
+}
+
+impl Display for FailureOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let option = match self {
+            FailureOption::All => "0",
+            FailureOption::Any => "1",
+            FailureOption::Dkim => "d",
+            FailureOption::Spf => "s",
+        };
+        write!(f, "{}", option)
+    }
+}
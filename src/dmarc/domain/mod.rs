@@ -0,0 +1,17 @@
+mod alignment;
+mod error;
+mod failure_option;
+mod parser;
+mod policy;
+mod record;
+mod report_uri;
+mod tag;
+
+pub use crate::common::syntax_error::{LabelSpan, Severity, SyntaxError};
+pub use alignment::Alignment;
+pub use error::DmarcError;
+pub use failure_option::FailureOption;
+pub use policy::Policy;
+pub use record::DmarcRecord;
+pub use report_uri::{Authorization, ReportUri};
+pub use tag::{Tag, TagValue};
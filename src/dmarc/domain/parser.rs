@@ -0,0 +1,352 @@
+//! Parser for the DMARC record grammar (RFC 7489 section 6.4)
+//!
+//! The parser never fails: tags that do not follow the grammar are returned as
+//! [`TagValue::Invalid`] with a [`SyntaxError`] pointing at the offending bytes.
+
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::dmarc::domain::{
+    Alignment, FailureOption, LabelSpan, Policy, ReportUri, SyntaxError, Tag, TagValue,
+};
+
+/// Parse the tags of a raw DMARC record
+pub fn parse_record(raw_rdata: &str) -> Vec<Tag> {
+    let parser = Parser { raw_rdata };
+
+    split_spans(raw_rdata, 0..raw_rdata.len(), ';')
+        .into_iter()
+        .map(|span| parser.tag(span))
+        .collect()
+}
+
+/// The trimmed, non-empty parts of the span separated by the delimiter
+fn split_spans(raw_rdata: &str, span: Range<usize>, delimiter: char) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut start = span.start;
+
+    for (index, c) in raw_rdata[span.clone()].char_indices() {
+        if c == delimiter {
+            spans.push(start..span.start + index);
+            start = span.start + index + c.len_utf8();
+        }
+    }
+    spans.push(start..span.end);
+
+    spans
+        .into_iter()
+        .map(|span| trim_span(raw_rdata, span))
+        .filter(|span| !span.is_empty())
+        .collect()
+}
+
+/// The span without leading and trailing whitespace (`WSP`)
+fn trim_span(raw_rdata: &str, span: Range<usize>) -> Range<usize> {
+    let is_wsp = |c: char| c == ' ' || c == '\t';
+    let value = &raw_rdata[span.clone()];
+    let start = span.start + (value.len() - value.trim_start_matches(is_wsp).len());
+    let end = span.end - (value.len() - value.trim_end_matches(is_wsp).len());
+
+    start..end.max(start)
+}
+
+struct Parser<'a> {
+    raw_rdata: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn tag(&self, span: Range<usize>) -> Tag {
+        let token = &self.raw_rdata[span.clone()];
+        let Some(position) = token.find('=') else {
+            return Tag {
+                name: token.to_ascii_lowercase(),
+                value: TagValue::Invalid(self.error(
+                    span.clone(),
+                    "Invalid tag",
+                    format!("'{}' has no value", token),
+                    "Write tags as name=value and separate them with ';'.",
+                )),
+                span,
+            };
+        };
+
+        let name_span = trim_span(self.raw_rdata, span.start..span.start + position);
+        let value_span = trim_span(self.raw_rdata, span.start + position + 1..span.end);
+        let name = self.raw_rdata[name_span.clone()].to_ascii_lowercase();
+
+        // tag-name = ALPHA *ALNUMPUNC
+        let is_valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let value = if is_valid_name {
+            self.value(&name, value_span)
+                .unwrap_or_else(TagValue::Invalid)
+        } else {
+            TagValue::Invalid(self.error(
+                name_span,
+                "Invalid tag name",
+                format!("'{}' is not a valid tag name", name),
+                "Tag names start with a letter followed by letters, digits or '_'.",
+            ))
+        };
+
+        Tag { name, value, span }
+    }
+
+    fn value(&self, name: &str, span: Range<usize>) -> Result<TagValue, Box<SyntaxError>> {
+        let value = &self.raw_rdata[span.clone()];
+
+        match name {
+            // The version is case-sensitive, unlike the other values
+            "v" if value == "DMARC1" => Ok(TagValue::Version),
+            "v" => Err(self.error(
+                span,
+                "Invalid DMARC version",
+                format!("'{}' is not a valid version", value),
+                "Use 'v=DMARC1'.",
+            )),
+            "p" => self.policy(span).map(TagValue::Policy),
+            "sp" => self.policy(span).map(TagValue::SubdomainPolicy),
+            "np" => self.policy(span).map(TagValue::NonExistentSubdomainPolicy),
+            "pct" => match u8::from_str(value) {
+                Ok(percentage) if percentage <= 100 => Ok(TagValue::Percentage(percentage)),
+                _ => Err(self.error(
+                    span,
+                    "Invalid percentage",
+                    format!("'{}' is not a percentage", value),
+                    "Use a whole number from 0 to 100.",
+                )),
+            },
+            "rua" => self.report_uris(span).map(TagValue::AggregateReportUris),
+            "ruf" => self.report_uris(span).map(TagValue::FailureReportUris),
+            "adkim" => self.alignment(span).map(TagValue::DkimAlignment),
+            "aspf" => self.alignment(span).map(TagValue::SpfAlignment),
+            "fo" => split_spans(self.raw_rdata, span.clone(), ':')
+                .into_iter()
+                .map(|span| {
+                    let option = &self.raw_rdata[span.clone()];
+                    FailureOption::from_str(option).map_err(|_| {
+                        self.error(
+                            span,
+                            "Invalid failure reporting option",
+                            format!("'{}' is not a failure reporting option", option),
+                            "Use '0', '1', 'd' or 's' and separate multiple options with ':'.",
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|options| self.non_empty(options, span))
+                .map(TagValue::FailureOptions),
+            "rf" => split_spans(self.raw_rdata, span.clone(), ':')
+                .into_iter()
+                .map(|span| {
+                    // Rf = keyword *(WSP ":" WSP keyword), keyword = ALPHA *ALNUMPUNC
+                    let format = &self.raw_rdata[span.clone()];
+                    if format.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && format
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    {
+                        Ok(format.to_ascii_lowercase())
+                    } else {
+                        Err(self.error(
+                            span,
+                            "Invalid report format",
+                            format!("'{}' is not a report format", format),
+                            "Use 'afrf' and separate multiple formats with ':'.",
+                        ))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|formats| self.non_empty(formats, span))
+                .map(TagValue::ReportFormats),
+            "ri" => u32::from_str(value)
+                .map(TagValue::ReportInterval)
+                .map_err(|_| {
+                    self.error(
+                        span,
+                        "Invalid report interval",
+                        format!("'{}' is not a number of seconds", value),
+                        "Use the number of seconds between aggregate reports (e.g. 86400).",
+                    )
+                }),
+            _ => Ok(TagValue::Unknown(value.to_owned())),
+        }
+    }
+
+    fn policy(&self, span: Range<usize>) -> Result<Policy, Box<SyntaxError>> {
+        let value = &self.raw_rdata[span.clone()];
+        Policy::from_str(value).map_err(|_| {
+            self.error(
+                span,
+                "Invalid policy",
+                format!("'{}' is not a policy", value),
+                "Use 'none', 'quarantine' or 'reject'.",
+            )
+        })
+    }
+
+    fn alignment(&self, span: Range<usize>) -> Result<Alignment, Box<SyntaxError>> {
+        let value = &self.raw_rdata[span.clone()];
+        Alignment::from_str(value).map_err(|_| {
+            self.error(
+                span,
+                "Invalid alignment mode",
+                format!("'{}' is not an alignment mode", value),
+                "Use 'r' for relaxed or 's' for strict alignment.",
+            )
+        })
+    }
+
+    /// `dmarc-uri *(*WSP "," *WSP dmarc-uri)`, `dmarc-uri = URI [ "!" 1*DIGIT [ "k" / "m" / "g" / "t" ] ]`
+    fn report_uris(&self, span: Range<usize>) -> Result<Vec<ReportUri>, Box<SyntaxError>> {
+        let uris = split_spans(self.raw_rdata, span.clone(), ',')
+            .into_iter()
+            .map(|span| {
+                let value = &self.raw_rdata[span.clone()];
+                let (uri, max_size) = match value.split_once('!') {
+                    Some((uri, max_size)) => (uri, Some(max_size.to_owned())),
+                    None => (value, None),
+                };
+
+                // scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." ) (RFC 3986 section 3.1)
+                let is_uri = uri.split_once(':').is_some_and(|(scheme, rest)| {
+                    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && scheme
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+                        && !rest.is_empty()
+                });
+                if !is_uri {
+                    return Err(self.error(
+                        span,
+                        "Invalid report URI",
+                        format!("'{}' is not a URI", value),
+                        "Use a URI like 'mailto:dmarc@example.com' and separate multiple URIs with ','.",
+                    ));
+                }
+
+                Ok(ReportUri {
+                    uri: uri.to_owned(),
+                    max_size,
+                    span,
+                    authorization: None,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.non_empty(uris, span)
+    }
+
+    fn non_empty<T>(&self, values: Vec<T>, span: Range<usize>) -> Result<Vec<T>, Box<SyntaxError>> {
+        if values.is_empty() {
+            return Err(self.error(
+                span,
+                "Missing tag value",
+                "The value is empty",
+                "Remove the tag or give it a value.",
+            ));
+        }
+        Ok(values)
+    }
+
+    fn error(
+        &self,
+        span: Range<usize>,
+        message: impl Into<String>,
+        label: impl Into<String>,
+        help: impl Into<String>,
+    ) -> Box<SyntaxError> {
+        Box::new(
+            SyntaxError::new(message)
+                .with_src(self.raw_rdata)
+                .with_src_labels(vec![LabelSpan::at(span, label.into())])
+                .with_help(help),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn error_label(tag: &Tag) -> (usize, usize) {
+        let TagValue::Invalid(err) = &tag.value else {
+            panic!("expected a syntax error");
+        };
+        let label = err.src_labels.as_ref().unwrap().last().unwrap();
+        (label.offset(), label.len())
+    }
+
+    #[test]
+    fn it_should_parse_all_tags_with_spans() {
+        let tags = parse_record(
+            "v=DMARC1; p=reject ;sp=quarantine; np=none; pct=50; \
+             rua=mailto:a@example.com!10m, mailto:b@example.org; ruf=mailto:f@example.com; \
+             adkim=s; aspf=r; fo=1:d; rf=afrf; ri=3600; x=y;",
+        );
+
+        assert_eq!(tags.len(), 13);
+        assert!(matches!(tags[0].value, TagValue::Version));
+        assert!(matches!(tags[1].value, TagValue::Policy(Policy::Reject)));
+        assert_eq!(tags[1].span, 10..18);
+        assert!(matches!(
+            tags[2].value,
+            TagValue::SubdomainPolicy(Policy::Quarantine)
+        ));
+        assert!(matches!(
+            tags[3].value,
+            TagValue::NonExistentSubdomainPolicy(Policy::None)
+        ));
+        assert!(matches!(tags[4].value, TagValue::Percentage(50)));
+        let TagValue::AggregateReportUris(uris) = &tags[5].value else {
+            panic!("expected report URIs");
+        };
+        assert_eq!(uris[0].uri, "mailto:a@example.com");
+        assert_eq!(uris[0].max_size.as_deref(), Some("10m"));
+        assert_eq!(uris[1].mailto_address(), Some("b@example.org"));
+        assert!(matches!(tags[6].value, TagValue::FailureReportUris(_)));
+        assert!(matches!(
+            tags[7].value,
+            TagValue::DkimAlignment(Alignment::Strict)
+        ));
+        assert!(matches!(
+            tags[8].value,
+            TagValue::SpfAlignment(Alignment::Relaxed)
+        ));
+        assert!(matches!(
+            &tags[9].value,
+            TagValue::FailureOptions(options) if *options == [FailureOption::Any, FailureOption::Dkim]
+        ));
+        assert!(
+            matches!(&tags[10].value, TagValue::ReportFormats(formats) if formats == &["afrf"])
+        );
+        assert!(matches!(tags[11].value, TagValue::ReportInterval(3600)));
+        assert!(matches!(&tags[12].value, TagValue::Unknown(value) if value == "y"));
+    }
+
+    #[test]
+    fn it_should_point_at_invalid_values() {
+        let tags = parse_record("v=DMARC1; p=block; pct=101; rua=dmarc@example.com; fo=0:x");
+
+        assert_eq!(error_label(&tags[1]), (12, 5));
+        assert_eq!(error_label(&tags[2]), (23, 3));
+        assert_eq!(error_label(&tags[3]), (32, 17));
+        assert_eq!(error_label(&tags[4]), (56, 1));
+    }
+
+    #[test]
+    fn it_should_reject_tag_without_value() {
+        let tags = parse_record("v=DMARC1; reject");
+
+        assert_eq!(error_label(&tags[1]), (10, 6));
+    }
+
+    #[test]
+    fn it_should_require_case_sensitive_version() {
+        let tags = parse_record("v=dmarc1; P=REJECT");
+
+        assert_eq!(error_label(&tags[0]), (2, 6));
+        assert_eq!(tags[1].name, "p");
+        assert!(matches!(tags[1].value, TagValue::Policy(Policy::Reject)));
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The policy the domain owner requests for mail that fails DMARC (RFC 7489 section 6.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// The domain owner requests no specific action
+    None,
+    /// Mail that fails DMARC should be treated as suspicious
+    Quarantine,
+    /// Mail that fails DMARC should be rejected
+    Reject,
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Policy::None),
+            "quarantine" => Ok(Policy::Quarantine),
+            "reject" => Ok(Policy::Reject),
+            _ => Err(format!("Unknown policy: {}", s)),
+        }
+    }
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let policy = match self {
+            Policy::None => "none",
+            Policy::Quarantine => "quarantine",
+            Policy::Reject => "reject",
+        };
+        write!(f, "{}", policy)
+    }
+}
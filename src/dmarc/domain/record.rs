@@ -0,0 +1,128 @@
+use crate::dmarc::domain::parser::parse_record;
+use crate::dmarc::domain::{Alignment, FailureOption, Policy, ReportUri, Tag, TagValue};
+
+/// A parsed DMARC record
+pub struct DmarcRecord {
+    /// The tags in the order of the record
+    pub tags: Vec<Tag>,
+
+    /// RDATA of a single DNS TXT resource record
+    pub raw_rdata: String,
+}
+
+impl DmarcRecord {
+    /// Returns true if the text starts with the version tag "v=DMARC1" (RFC 7489 section 6.6.3)
+    pub fn is_dmarc_record(text: &str) -> bool {
+        let version = text.split(';').next().unwrap_or_default();
+        match version.split_once('=') {
+            Some((name, value)) => name.trim() == "v" && value.trim() == "DMARC1",
+            None => false,
+        }
+    }
+
+    /// Parse the raw record without any network access
+    pub fn parse(raw_rdata: &str) -> Self {
+        DmarcRecord {
+            tags: parse_record(raw_rdata),
+            raw_rdata: raw_rdata.to_owned(),
+        }
+    }
+
+    /// The value of the first valid tag selected by `value`
+    fn find<'a, T>(&'a self, value: impl Fn(&'a TagValue) -> Option<T>) -> Option<T> {
+        self.tags.iter().find_map(|tag| value(&tag.value))
+    }
+
+    pub fn policy(&self) -> Option<Policy> {
+        self.find(|value| match value {
+            TagValue::Policy(policy) => Some(*policy),
+            _ => None,
+        })
+    }
+
+    /// The policy for subdomains, defaults to the policy of the domain
+    pub fn subdomain_policy(&self) -> Option<Policy> {
+        self.find(|value| match value {
+            TagValue::SubdomainPolicy(policy) => Some(*policy),
+            _ => None,
+        })
+        .or_else(|| self.policy())
+    }
+
+    /// The policy for non-existent subdomains, defaults to the policy for subdomains
+    pub fn non_existent_subdomain_policy(&self) -> Option<Policy> {
+        self.find(|value| match value {
+            TagValue::NonExistentSubdomainPolicy(policy) => Some(*policy),
+            _ => None,
+        })
+        .or_else(|| self.subdomain_policy())
+    }
+
+    /// The percentage of mail the policy is applied to, defaults to 100
+    pub fn percentage(&self) -> u8 {
+        self.find(|value| match value {
+            TagValue::Percentage(percentage) => Some(*percentage),
+            _ => None,
+        })
+        .unwrap_or(100)
+    }
+
+    pub fn aggregate_report_uris(&self) -> &[ReportUri] {
+        self.find(|value| match value {
+            TagValue::AggregateReportUris(uris) => Some(uris.as_slice()),
+            _ => None,
+        })
+        .unwrap_or_default()
+    }
+
+    pub fn failure_report_uris(&self) -> &[ReportUri] {
+        self.find(|value| match value {
+            TagValue::FailureReportUris(uris) => Some(uris.as_slice()),
+            _ => None,
+        })
+        .unwrap_or_default()
+    }
+
+    pub fn dkim_alignment(&self) -> Alignment {
+        self.find(|value| match value {
+            TagValue::DkimAlignment(alignment) => Some(*alignment),
+            _ => None,
+        })
+        .unwrap_or_default()
+    }
+
+    pub fn spf_alignment(&self) -> Alignment {
+        self.find(|value| match value {
+            TagValue::SpfAlignment(alignment) => Some(*alignment),
+            _ => None,
+        })
+        .unwrap_or_default()
+    }
+
+    /// When failure reports are requested, defaults to "0"
+    pub fn failure_options(&self) -> Vec<FailureOption> {
+        self.find(|value| match value {
+            TagValue::FailureOptions(options) => Some(options.to_owned()),
+            _ => None,
+        })
+        .unwrap_or_else(|| vec![FailureOption::All])
+    }
+
+    /// The formats of failure reports, defaults to "afrf"
+    pub fn report_formats(&self) -> Vec<String> {
+        self.find(|value| match value {
+            TagValue::ReportFormats(formats) => Some(formats.to_owned()),
+            _ => None,
+        })
+        .unwrap_or_else(|| vec!["afrf".to_owned()])
+    }
+
+    /// Seconds between aggregate reports, defaults to one day
+    pub fn report_interval(&self) -> u32 {
+        self.find(|value| match value {
+            TagValue::ReportInterval(interval) => Some(*interval),
+            _ => None,
+        })
+        .unwrap_or(86400)
+    }
+}
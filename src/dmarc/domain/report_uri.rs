@@ -0,0 +1,87 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::dns::domain::DnsError;
+
+/// A destination for reports with an optional maximum report size (RFC 7489 section 6.2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportUri {
+    /// The URI (e.g. "mailto:dmarc@example.com")
+    pub uri: String,
+
+    /// The maximum size after the "!" as written (e.g. "10m")
+    pub max_size: Option<String>,
+
+    /// The byte range of the URI in the raw record
+    pub span: Range<usize>,
+
+    /// Whether the destination accepts the reports, none if it is not external or not resolved
+    pub authorization: Option<Authorization>,
+}
+
+/// The answer of a destination outside the domain to whether it accepts its reports
+/// (RFC 7489 section 7.1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Authorization {
+    /// The destination publishes a "v=DMARC1" record for the domain
+    Authorized,
+    /// The destination publishes no record for the domain, reports will not be sent
+    Unauthorized {
+        /// The queried name (e.g. "example.com._report._dmarc.example.net")
+        name: String,
+    },
+    /// The query for the record failed
+    LookupFailed(DnsError),
+}
+
+impl ReportUri {
+    /// The address of a mailto URI without the scheme and query
+    pub fn mailto_address(&self) -> Option<&str> {
+        let (scheme, address) = self.uri.split_once(':')?;
+        if !scheme.eq_ignore_ascii_case("mailto") {
+            return None;
+        }
+        Some(address.split('?').next().unwrap_or_default())
+    }
+
+    /// The domain name of a mailto URI (e.g. "example.com")
+    pub fn mailto_domain(&self) -> Option<String> {
+        let (_, domain_name) = self.mailto_address()?.rsplit_once('@')?;
+        Some(domain_name.trim_end_matches('.').to_ascii_lowercase())
+    }
+
+    /// The maximum size in bytes, none without or with an invalid size
+    /// (`1*DIGIT [ "k" / "m" / "g" / "t" ]`)
+    pub fn max_size_bytes(&self) -> Option<u64> {
+        let max_size = self.max_size.as_deref()?;
+        let (digits, exponent) = match max_size.char_indices().last()? {
+            (index, unit) if unit.is_ascii_alphabetic() => {
+                let exponent = match unit.to_ascii_lowercase() {
+                    'k' => 1,
+                    'm' => 2,
+                    'g' => 3,
+                    't' => 4,
+                    _ => return None,
+                };
+                (&max_size[..index], exponent)
+            }
+            _ => (max_size, 0),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        digits
+            .parse::<u64>()
+            .ok()?
+            .checked_mul(1024u64.pow(exponent))
+    }
+}
+
+impl Display for ReportUri {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.max_size {
+            Some(max_size) => write!(f, "{}!{}", self.uri, max_size),
+            None => write!(f, "{}", self.uri),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::ops::Range;
+
+use crate::dmarc::domain::{Alignment, FailureOption, Policy, ReportUri, SyntaxError};
+
+/// A tag of a DMARC record (e.g. "p=reject")
+pub struct Tag {
+    /// The lowercase name of the tag (e.g. "p")
+    pub name: String,
+    pub value: TagValue,
+
+    /// The byte range of the tag in the raw record
+    pub span: Range<usize>,
+}
+
+/// The parsed value of a tag (RFC 7489 section 6.3)
+pub enum TagValue {
+    /// "v", always "DMARC1"
+    Version,
+    /// "p"
+    Policy(Policy),
+    /// "sp", the policy for subdomains
+    SubdomainPolicy(Policy),
+    /// "np", the policy for non-existent subdomains
+    NonExistentSubdomainPolicy(Policy),
+    /// "pct", the percentage of mail the policy is applied to
+    Percentage(u8),
+    /// "rua", where aggregate reports are sent to
+    AggregateReportUris(Vec<ReportUri>),
+    /// "ruf", where failure reports are sent to
+    FailureReportUris(Vec<ReportUri>),
+    /// "adkim"
+    DkimAlignment(Alignment),
+    /// "aspf"
+    SpfAlignment(Alignment),
+    /// "fo"
+    FailureOptions(Vec<FailureOption>),
+    /// "rf", the formats of failure reports (e.g. "afrf")
+    ReportFormats(Vec<String>),
+    /// "ri", the interval between aggregate reports in seconds
+    ReportInterval(u32),
+    /// A tag that is not defined, it must be ignored
+    Unknown(String),
+    /// A tag that does not follow the DMARC grammar
+    Invalid(Box<SyntaxError>),
+}
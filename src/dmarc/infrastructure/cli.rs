@@ -0,0 +1,57 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use clap::Args;
+
+use crate::{
+    common::{
+        cli::{CliCommand, FailOn},
+        outcome::Outcome,
+        presenter::Presenter,
+    },
+    dmarc::core::check::{
+        DmarcSummary, SummaryDmarcQuery, SummaryDmarcTerminalPresenter, SummaryDmarcUseCase,
+        SummaryDmarcUseCaseImpl, SummaryDmarcWithDetailTerminalPresenter,
+    },
+    dmarc::domain::DmarcError,
+    dns::core::dns_resolver::DnsResolver,
+};
+
+#[derive(Args)]
+pub struct Dmarc {
+    /// Output with details
+    #[arg(short, long)]
+    pub detail: bool,
+
+    /// Use record value instead of querying it from DNS
+    /// (useful for testing)
+    #[arg(short, long)]
+    pub record: Option<String>,
+
+    /// Lowest severity of the findings that makes the check exit unsuccessfully
+    #[arg(long, value_enum, default_value_t)]
+    pub fail_on: FailOn,
+
+    /// Domain name to check
+    pub domain: String,
+}
+
+#[async_trait(?Send)]
+impl CliCommand<Dmarc> for Dmarc {
+    async fn execute(&self, dns_resolver: &dyn DnsResolver) -> Result<Outcome, Box<dyn Error>> {
+        let presenter: Box<dyn Presenter<DmarcSummary, DmarcError>> = if self.detail {
+            Box::new(SummaryDmarcWithDetailTerminalPresenter::new())
+        } else {
+            Box::new(SummaryDmarcTerminalPresenter::new())
+        };
+        let mut summary_dmarc_use_case = SummaryDmarcUseCaseImpl::new(dns_resolver);
+
+        let query = SummaryDmarcQuery {
+            domain_name: self.domain.to_owned(),
+            record: self.record.to_owned(),
+        };
+        let outcome = summary_dmarc_use_case.execute(&query, presenter).await;
+
+        Ok(self.fail_on.apply(outcome))
+    }
+}
@@ -0,0 +1,12 @@
+//! DMARC module
+//!
+//! This module contains all the DMARC related code.
+//!
+//! It is divided into three submodules:
+//! - `core`: contains the core logic
+//! - `domain`: contains the domain logic
+//! - `infrastructure`: contains the infrastructure logic
+
+pub mod core;
+pub mod domain;
+pub mod infrastructure;
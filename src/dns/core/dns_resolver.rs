@@ -1,21 +1,35 @@
-use crate::dns::domain::{ARecord, MxRecord, TxtRecord};
-use std::error::Error;
+use crate::dns::domain::{ARecord, AaaaRecord, DnsError, MxRecord, PtrRecord, TxtRecord};
+use async_trait::async_trait;
+use std::net::IpAddr;
 
-pub trait DnsResolver {
-    /// Query the TXT record of a domain name.
-    fn query_a(&mut self, query: &ARecordQuery) -> Result<ARecord, Box<dyn Error>>;
+/// A resolver shared by all lookups, queries may run concurrently
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait DnsResolver: Send + Sync {
+    /// Query the A record of a domain name.
+    async fn query_a(&self, query: &ARecordQuery) -> Result<ARecord, DnsError>;
+
+    /// Query the AAAA record of a domain name.
+    async fn query_aaaa(&self, query: &AaaaRecordQuery) -> Result<AaaaRecord, DnsError>;
 
     /// Query the TXT record of a domain name.
-    fn query_txt(&mut self, query: &TxtRecordQuery) -> Result<TxtRecord, Box<dyn Error>>;
+    async fn query_txt(&self, query: &TxtRecordQuery) -> Result<TxtRecord, DnsError>;
 
     /// Query the MX record of a domain name.
-    fn query_mx(&mut self, query: &MxRecordQuery) -> Result<MxRecord, Box<dyn Error>>;
+    async fn query_mx(&self, query: &MxRecordQuery) -> Result<MxRecord, DnsError>;
+
+    /// Query the PTR record of an IP address.
+    async fn query_ptr(&self, query: &PtrRecordQuery) -> Result<PtrRecord, DnsError>;
 }
 
 pub struct ARecordQuery {
     pub domain_name: String,
 }
 
+pub struct AaaaRecordQuery {
+    pub domain_name: String,
+}
+
 pub struct TxtRecordQuery {
     pub domain_name: String,
 }
@@ -23,3 +37,7 @@ pub struct TxtRecordQuery {
 pub struct MxRecordQuery {
     pub domain_name: String,
 }
+
+pub struct PtrRecordQuery {
+    pub ip_address: IpAddr,
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Why a DNS query returned no records, with the queried name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DnsError {
+    /// The domain name does not exist (NXDOMAIN)
+    NxDomain {
+        name: String,
+        /// Seconds the answer may be cached, taken from the SOA record (RFC 2308 section 5)
+        negative_ttl: Option<u32>,
+    },
+
+    /// The domain name exists, but has no records of the queried type
+    NoData {
+        name: String,
+        /// Seconds the answer may be cached, taken from the SOA record (RFC 2308 section 5)
+        negative_ttl: Option<u32>,
+    },
+
+    /// The server failed to answer (SERVFAIL, another error code or a malformed response)
+    ServFail(String),
+
+    /// No server answered in time
+    Timeout(String),
+
+    /// The name is not a valid domain name
+    InvalidName(String),
+}
+
+impl DnsError {
+    /// The queried name
+    pub fn name(&self) -> &str {
+        match self {
+            DnsError::NxDomain { name, .. }
+            | DnsError::NoData { name, .. }
+            | DnsError::ServFail(name)
+            | DnsError::Timeout(name)
+            | DnsError::InvalidName(name) => name,
+        }
+    }
+
+    /// Returns true if the query was answered, but without records
+    pub fn is_void(&self) -> bool {
+        matches!(self, DnsError::NxDomain { .. } | DnsError::NoData { .. })
+    }
+
+    /// Seconds a negative answer may be cached, none if it must not be cached
+    pub fn negative_ttl(&self) -> Option<u32> {
+        match self {
+            DnsError::NxDomain { negative_ttl, .. } | DnsError::NoData { negative_ttl, .. } => {
+                *negative_ttl
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if the query may succeed when it is retried later
+    pub fn is_temporary(&self) -> bool {
+        matches!(self, DnsError::ServFail(_) | DnsError::Timeout(_))
+    }
+}
+
+impl Display for DnsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DnsError::NxDomain { name, .. } => write!(f, "'{}' does not exist (NXDOMAIN)", name),
+            DnsError::NoData { name, .. } => write!(f, "No records found for '{}' (NODATA)", name),
+            DnsError::ServFail(name) => write!(f, "Server failure for '{}' (SERVFAIL)", name),
+            DnsError::Timeout(name) => write!(f, "Query for '{}' timed out", name),
+            DnsError::InvalidName(name) => write!(f, "'{}' is not a valid domain name", name),
+        }
+    }
+}
+
+impl Error for DnsError {}
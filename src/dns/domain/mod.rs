@@ -1,3 +1,7 @@
+mod error;
+mod public_suffix_list;
 mod record;
 
-pub use record::{ARecord, MxRecord, TxtRecord};
+pub use error::DnsError;
+pub use public_suffix_list::{organizational_domain, use_public_suffix_list, PublicSuffixList};
+pub use record::{ARecord, AaaaRecord, MxRecord, PtrRecord, TxtRecord};
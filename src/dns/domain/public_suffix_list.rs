@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// The rules of the Public Suffix List (https://publicsuffix.org/list/), including the
+/// private domains, to find the organizational domain of a domain name (RFC 7489 section 3.2)
+pub struct PublicSuffixList {
+    rules: HashSet<String>,
+
+    /// Exception rules without the leading "!"
+    exceptions: HashSet<String>,
+}
+
+/// The list used by `organizational_domain`, the embedded snapshot unless another one was set
+static PUBLIC_SUFFIX_LIST: OnceLock<PublicSuffixList> = OnceLock::new();
+
+/// Use the list instead of the embedded snapshot, must be called before the first lookup
+pub fn use_public_suffix_list(list: PublicSuffixList) -> Result<(), String> {
+    PUBLIC_SUFFIX_LIST
+        .set(list)
+        .map_err(|_| "The public suffix list is already in use".to_owned())
+}
+
+/// The registered domain a domain name belongs to (e.g. "example.co.uk" for
+/// "mail.example.co.uk"), none if the domain name is a public suffix
+pub fn organizational_domain(domain_name: &str) -> Option<String> {
+    PUBLIC_SUFFIX_LIST
+        .get_or_init(PublicSuffixList::embedded)
+        .organizational_domain(domain_name)
+}
+
+impl PublicSuffixList {
+    /// The snapshot of the list that is built into the binary
+    pub fn embedded() -> Self {
+        Self::parse(include_str!("public_suffix_list.dat"))
+    }
+
+    /// Parse the rules of a list in the format of `public_suffix_list.dat`,
+    /// internationalized rules also match their punycode form
+    pub fn parse(text: &str) -> Self {
+        let mut list = PublicSuffixList {
+            rules: HashSet::new(),
+            exceptions: HashSet::new(),
+        };
+
+        // Each line is only read up to the first whitespace
+        let rules = text
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|rule| !rule.starts_with("//"))
+            .map(str::to_lowercase);
+        for rule in rules {
+            let (rules, rule) = match rule.strip_prefix('!') {
+                Some(exception) => (&mut list.exceptions, exception.to_owned()),
+                None => (&mut list.rules, rule),
+            };
+            if let Some(ascii_rule) = to_ascii(&rule) {
+                rules.insert(ascii_rule);
+            }
+            rules.insert(rule);
+        }
+
+        list
+    }
+
+    /// Returns true if the list has no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.exceptions.is_empty()
+    }
+
+    /// The public suffix plus one label, none if the domain name is a public suffix
+    pub fn organizational_domain(&self, domain_name: &str) -> Option<String> {
+        let domain_name = domain_name.trim_end_matches('.').to_lowercase();
+        let labels = domain_name.split('.').collect::<Vec<_>>();
+        if labels.iter().any(|label| label.is_empty()) {
+            return None;
+        }
+
+        let public_suffix_len = self.public_suffix_len(&labels);
+        if labels.len() <= public_suffix_len {
+            return None;
+        }
+        Some(labels[labels.len() - public_suffix_len - 1..].join("."))
+    }
+
+    /// The number of labels of the public suffix of the prevailing rule
+    fn public_suffix_len(&self, labels: &[&str]) -> usize {
+        let suffix = |index: usize| labels[index..].join(".");
+
+        // An exception rule prevails, its public suffix is the rule without the leftmost label
+        if let Some(index) =
+            (0..labels.len()).find(|&index| self.exceptions.contains(&suffix(index)))
+        {
+            return labels.len() - index - 1;
+        }
+
+        // Otherwise the rule with the most labels prevails, "*" if no rule matches
+        (0..labels.len())
+            .find(|&index| {
+                self.rules.contains(&suffix(index))
+                    || (index + 1 < labels.len()
+                        && self.rules.contains(&format!("*.{}", suffix(index + 1))))
+            })
+            .map(|index| labels.len() - index)
+            .unwrap_or(1)
+    }
+}
+
+/// The punycode form of a rule with non-ASCII labels (RFC 3492), none for ASCII rules
+fn to_ascii(rule: &str) -> Option<String> {
+    if rule.is_ascii() {
+        return None;
+    }
+
+    rule.split('.')
+        .map(|label| match label.is_ascii() {
+            true => Some(label.to_owned()),
+            false => punycode(label).map(|label| format!("xn--{}", label)),
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|labels| labels.join("."))
+}
+
+/// Encode a label with the Punycode algorithm (RFC 3492 section 6.3)
+fn punycode(label: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+
+    let code_points = label.chars().map(u32::from).collect::<Vec<_>>();
+    let mut output = label.chars().filter(char::is_ascii).collect::<String>();
+    let basic_count = output.len() as u32;
+    if basic_count > 0 {
+        output.push('-');
+    }
+
+    let digit = |digit: u32| match digit {
+        0..=25 => char::from(b'a' + digit as u8),
+        _ => char::from(b'0' + (digit - 26) as u8),
+    };
+
+    let (mut n, mut delta, mut bias) = (128, 0u32, 72);
+    let mut handled_count = basic_count;
+    while (handled_count as usize) < code_points.len() {
+        let m = *code_points.iter().filter(|&&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled_count + 1)?)?;
+        n = m;
+
+        for &c in &code_points {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = k.saturating_sub(bias).clamp(T_MIN, T_MAX);
+                    if q < t {
+                        break;
+                    }
+                    output.push(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(digit(q));
+                bias = adapt(delta, handled_count + 1, handled_count == basic_count);
+                delta = 0;
+                handled_count += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+
+    Some(output)
+}
+
+/// Bias adaptation function (RFC 3492 section 6.1)
+fn adapt(delta: u32, point_count: u32, is_first: bool) -> u32 {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+
+    let mut delta = if is_first { delta / DAMP } else { delta / 2 };
+    delta += delta / point_count;
+
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_find_organizational_domain_of_multi_label_suffix() {
+        let list = PublicSuffixList::embedded();
+
+        assert_eq!(
+            list.organizational_domain("mail.Example.CO.UK."),
+            Some("example.co.uk".to_owned())
+        );
+        assert_eq!(
+            list.organizational_domain("example.com"),
+            Some("example.com".to_owned())
+        );
+        assert_eq!(list.organizational_domain("co.uk"), None);
+    }
+
+    #[test]
+    fn it_should_find_organizational_domain_of_private_suffix() {
+        let list = PublicSuffixList::embedded();
+
+        assert_eq!(
+            list.organizational_domain("www.user.github.io"),
+            Some("user.github.io".to_owned())
+        );
+        assert_eq!(list.organizational_domain("github.io"), None);
+    }
+
+    #[test]
+    fn it_should_apply_wildcard_and_exception_rules() {
+        let list = PublicSuffixList::parse("// comment\n*.ck\n!www.ck\n公司.cn\n");
+
+        assert_eq!(
+            list.organizational_domain("a.b.example.ck"),
+            Some("b.example.ck".to_owned())
+        );
+        assert_eq!(
+            list.organizational_domain("a.www.ck"),
+            Some("www.ck".to_owned())
+        );
+        assert_eq!(
+            list.organizational_domain("mail.example.xn--55qx5d.cn"),
+            Some("example.xn--55qx5d.cn".to_owned())
+        );
+        // Domain names without a matching rule have a single label public suffix
+        assert_eq!(
+            list.organizational_domain("mail.example.test"),
+            Some("example.test".to_owned())
+        );
+    }
+
+    #[test]
+    fn it_should_encode_punycode() {
+        assert_eq!(punycode("公司").as_deref(), Some("55qx5d"));
+        assert_eq!(punycode("bücher").as_deref(), Some("bcher-kva"));
+    }
+}
@@ -1,13 +1,53 @@
-use std::net::IpAddr;
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, Ipv6Addr};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ARecord {
-    pub ip_addresses: Vec<IpAddr>,
+    pub ip_addresses: Vec<Ipv4Addr>,
+
+    /// Seconds the answer may be cached, the lowest TTL of its records
+    pub ttl: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AaaaRecord {
+    pub ip_addresses: Vec<Ipv6Addr>,
+
+    /// Seconds the answer may be cached, the lowest TTL of its records
+    pub ttl: u32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TxtRecord {
-    pub records: Vec<String>,
+    /// The character-strings of each TXT resource record
+    pub records: Vec<Vec<String>>,
+
+    /// Seconds the answer may be cached, the lowest TTL of its records
+    pub ttl: u32,
 }
 
+impl TxtRecord {
+    /// The text of each record, with its character-strings concatenated without spaces
+    pub fn texts(&self) -> Vec<String> {
+        self.records
+            .iter()
+            .map(|character_strings| character_strings.concat())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MxRecord {
     pub exchanges: Vec<String>,
+
+    /// Seconds the answer may be cached, the lowest TTL of its records
+    pub ttl: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtrRecord {
+    pub names: Vec<String>,
+
+    /// Seconds the answer may be cached, the lowest TTL of its records
+    pub ttl: u32,
 }
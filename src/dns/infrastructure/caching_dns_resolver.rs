@@ -0,0 +1,319 @@
+use crate::dns::core::dns_resolver::{
+    ARecordQuery, AaaaRecordQuery, DnsResolver, MxRecordQuery, PtrRecordQuery, TxtRecordQuery,
+};
+use crate::dns::domain::{ARecord, AaaaRecord, DnsError, MxRecord, PtrRecord, TxtRecord};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Caches the answers of another resolver for as long as their TTL allows,
+/// including negative answers with a SOA record (RFC 2308)
+pub struct CachingDnsResolver {
+    inner: Box<dyn DnsResolver>,
+    a: Cache<ARecord>,
+    aaaa: Cache<AaaaRecord>,
+    txt: Cache<TxtRecord>,
+    mx: Cache<MxRecord>,
+    ptr: Cache<PtrRecord>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+/// Answers by query name, with the instant they expire.
+///
+/// Each name has its own async lock, held while its query is in flight.
+type Cache<T> = Mutex<HashMap<String, Arc<tokio::sync::Mutex<Option<Entry<T>>>>>>;
+
+type Entry<T> = (Result<T, DnsError>, Instant);
+
+impl CachingDnsResolver {
+    pub fn new(inner: Box<dyn DnsResolver>) -> Self {
+        CachingDnsResolver {
+            inner,
+            a: Mutex::default(),
+            aaaa: Mutex::default(),
+            txt: Mutex::default(),
+            mx: Mutex::default(),
+            ptr: Mutex::default(),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Answer from the cache if there is an unexpired entry, otherwise run the query and cache its answer.
+    ///
+    /// Concurrent queries for the same name wait for the answer of the first one.
+    async fn cached<T: Clone>(
+        &self,
+        cache: &Cache<T>,
+        name: &str,
+        ttl: fn(&T) -> u32,
+        query: impl Future<Output = Result<T, DnsError>>,
+    ) -> Result<T, DnsError> {
+        let key = name.trim_end_matches('.').to_lowercase();
+        let entry = cache
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let mut entry = entry.lock().await;
+        if let Some((answer, _)) = entry
+            .as_ref()
+            .filter(|(_, expires_at)| Instant::now() < *expires_at)
+        {
+            log::debug!("Cache hit for '{}'", key);
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return answer.clone();
+        }
+
+        log::debug!("Cache miss for '{}'", key);
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let answer = query.await;
+        let seconds = match &answer {
+            Ok(record) => Some(ttl(record)),
+            Err(err) => err.negative_ttl(),
+        };
+        *entry = match seconds {
+            Some(seconds) if seconds > 0 => {
+                let expires_at = Instant::now() + Duration::from_secs(seconds.into());
+                Some((answer.clone(), expires_at))
+            }
+            _ => None,
+        };
+        answer
+    }
+}
+
+impl Drop for CachingDnsResolver {
+    fn drop(&mut self) {
+        log::debug!(
+            "DNS cache statistics: {} hits, {} misses",
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed)
+        );
+    }
+}
+
+#[async_trait]
+impl DnsResolver for CachingDnsResolver {
+    async fn query_a(&self, query: &ARecordQuery) -> Result<ARecord, DnsError> {
+        let answer = self.inner.query_a(query);
+        self.cached(&self.a, &query.domain_name, |record| record.ttl, answer)
+            .await
+    }
+
+    async fn query_aaaa(&self, query: &AaaaRecordQuery) -> Result<AaaaRecord, DnsError> {
+        let answer = self.inner.query_aaaa(query);
+        self.cached(&self.aaaa, &query.domain_name, |record| record.ttl, answer)
+            .await
+    }
+
+    async fn query_txt(&self, query: &TxtRecordQuery) -> Result<TxtRecord, DnsError> {
+        let answer = self.inner.query_txt(query);
+        self.cached(&self.txt, &query.domain_name, |record| record.ttl, answer)
+            .await
+    }
+
+    async fn query_mx(&self, query: &MxRecordQuery) -> Result<MxRecord, DnsError> {
+        let answer = self.inner.query_mx(query);
+        self.cached(&self.mx, &query.domain_name, |record| record.ttl, answer)
+            .await
+    }
+
+    async fn query_ptr(&self, query: &PtrRecordQuery) -> Result<PtrRecord, DnsError> {
+        let answer = self.inner.query_ptr(query);
+        let name = query.ip_address.to_string();
+        self.cached(&self.ptr, &name, |record| record.ttl, answer)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::dns::core::dns_resolver::MockDnsResolver;
+
+    fn txt_query(domain_name: &str) -> TxtRecordQuery {
+        TxtRecordQuery {
+            domain_name: domain_name.to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_answer_repeated_query_from_cache() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().times(1).returning(|_| {
+            Ok(TxtRecord {
+                records: vec![vec!["v=spf1 -all".to_owned()]],
+                ttl: 3600,
+            })
+        });
+        let caching_dns_resolver = CachingDnsResolver::new(Box::new(dns_resolver));
+
+        // Act
+        caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await
+            .unwrap();
+        let txt_record = caching_dns_resolver
+            .query_txt(&txt_query("Example.COM."))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(txt_record.texts(), vec!["v=spf1 -all"]);
+        assert_eq!(caching_dns_resolver.hits.load(Ordering::Relaxed), 1);
+        assert_eq!(caching_dns_resolver.misses.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_query_again_when_ttl_expired() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().times(2).returning(|_| {
+            Ok(TxtRecord {
+                records: vec![vec!["v=spf1 -all".to_owned()]],
+                ttl: 0,
+            })
+        });
+        let caching_dns_resolver = CachingDnsResolver::new(Box::new(dns_resolver));
+
+        // Act
+        caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await
+            .unwrap();
+        caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(caching_dns_resolver.hits.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn it_should_cache_negative_answer_with_soa() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().times(1).returning(|query| {
+            Err(DnsError::NxDomain {
+                name: query.domain_name.to_owned(),
+                negative_ttl: Some(300),
+            })
+        });
+        let caching_dns_resolver = CachingDnsResolver::new(Box::new(dns_resolver));
+
+        // Act
+        let _ = caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await;
+        let result = caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await;
+
+        // Assert
+        assert!(result.is_err_and(|err| err.is_void()));
+    }
+
+    #[tokio::test]
+    async fn it_should_not_cache_negative_answer_without_soa() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().times(2).returning(|query| {
+            Err(DnsError::NoData {
+                name: query.domain_name.to_owned(),
+                negative_ttl: None,
+            })
+        });
+        let caching_dns_resolver = CachingDnsResolver::new(Box::new(dns_resolver));
+
+        // Act
+        let _ = caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await;
+        let _ = caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await;
+
+        // Assert
+        assert_eq!(caching_dns_resolver.misses.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_cache_server_failure() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .times(2)
+            .returning(|query| Err(DnsError::ServFail(query.domain_name.to_owned())));
+        let caching_dns_resolver = CachingDnsResolver::new(Box::new(dns_resolver));
+
+        // Act
+        let _ = caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await;
+        let result = caching_dns_resolver
+            .query_txt(&txt_query("example.com"))
+            .await;
+
+        // Assert
+        assert!(result.is_err_and(|err| err.is_temporary()));
+    }
+
+    /// Answers TXT queries only after yielding, so concurrent queries are in flight together
+    struct SlowDnsResolver;
+
+    #[async_trait]
+    impl DnsResolver for SlowDnsResolver {
+        async fn query_a(&self, _query: &ARecordQuery) -> Result<ARecord, DnsError> {
+            unimplemented!()
+        }
+
+        async fn query_aaaa(&self, _query: &AaaaRecordQuery) -> Result<AaaaRecord, DnsError> {
+            unimplemented!()
+        }
+
+        async fn query_txt(&self, _query: &TxtRecordQuery) -> Result<TxtRecord, DnsError> {
+            tokio::task::yield_now().await;
+            Ok(TxtRecord {
+                records: vec![vec!["v=spf1 -all".to_owned()]],
+                ttl: 3600,
+            })
+        }
+
+        async fn query_mx(&self, _query: &MxRecordQuery) -> Result<MxRecord, DnsError> {
+            unimplemented!()
+        }
+
+        async fn query_ptr(&self, _query: &PtrRecordQuery) -> Result<PtrRecord, DnsError> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_share_the_answer_of_a_query_in_flight() {
+        // Arrange
+        let caching_dns_resolver = CachingDnsResolver::new(Box::new(SlowDnsResolver));
+        let query = txt_query("example.com");
+
+        // Act
+        let (first, second) = futures::future::join(
+            caching_dns_resolver.query_txt(&query),
+            caching_dns_resolver.query_txt(&query),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(first.unwrap().texts(), second.unwrap().texts());
+        assert_eq!(caching_dns_resolver.hits.load(Ordering::Relaxed), 1);
+        assert_eq!(caching_dns_resolver.misses.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,127 @@
+use std::error::Error;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::Args;
+
+use crate::dns::core::dns_resolver::DnsResolver;
+use crate::dns::infrastructure::caching_dns_resolver::CachingDnsResolver;
+use crate::dns::infrastructure::dns_resolver::{DnsResolverOptions, DomainDnsResolver};
+use crate::dns::infrastructure::session_dns_resolver::{RecordingDnsResolver, ReplayDnsResolver};
+use crate::dns::infrastructure::zone_file_dns_resolver::ZoneFileDnsResolver;
+
+/// Options of the DNS resolver shared by all commands
+#[derive(Args)]
+pub struct ResolverArgs {
+    /// Nameserver to query instead of the system ones, with an optional port
+    /// (e.g. 127.0.0.1:5353, can be repeated)
+    #[arg(long, global = true, value_name = "ADDRESS", value_parser = parse_nameserver)]
+    pub nameserver: Vec<SocketAddr>,
+
+    /// Seconds to wait for an answer of a nameserver
+    #[arg(long, global = true, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// How often to retry a query that got no answer
+    #[arg(long, global = true)]
+    pub retries: Option<usize>,
+
+    /// Query over TCP instead of UDP
+    #[arg(long, global = true)]
+    pub tcp: bool,
+
+    /// Query DNS for every lookup instead of caching answers for their TTL
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Answer all queries from the records of a zone file instead of DNS
+    /// (useful to check planned changes before publishing them)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        conflicts_with_all = ["nameserver", "timeout", "retries", "tcp"]
+    )]
+    pub zone_file: Option<PathBuf>,
+
+    /// Write every DNS query with its answer to a session file
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "zone_file")]
+    pub record_dns: Option<PathBuf>,
+
+    /// Answer all queries from a session file written by --record-dns instead of DNS
+    /// (useful to reproduce a diagnosis)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        conflicts_with_all = ["nameserver", "timeout", "retries", "tcp", "zone_file", "record_dns"]
+    )]
+    pub replay_dns: Option<PathBuf>,
+}
+
+impl ResolverArgs {
+    /// The resolver configured by the options
+    pub fn dns_resolver(&self) -> Result<Box<dyn DnsResolver>, Box<dyn Error>> {
+        if let Some(zone_file) = &self.zone_file {
+            return Ok(Box::new(ZoneFileDnsResolver::load(zone_file)?));
+        }
+        if let Some(session) = &self.replay_dns {
+            return Ok(Box::new(ReplayDnsResolver::load(session)?));
+        }
+
+        let mut dns_resolver: Box<dyn DnsResolver> =
+            Box::new(DomainDnsResolver::with_options(&DnsResolverOptions {
+                nameservers: self.nameserver.to_owned(),
+                timeout: self.timeout.map(Duration::from_secs),
+                retries: self.retries,
+                tcp: self.tcp,
+            }));
+        if let Some(session) = &self.record_dns {
+            dns_resolver = Box::new(RecordingDnsResolver::create(session, dns_resolver)?);
+        }
+        if !self.no_cache {
+            dns_resolver = Box::new(CachingDnsResolver::new(dns_resolver));
+        }
+
+        Ok(dns_resolver)
+    }
+}
+
+/// Parse a nameserver address, defaulting to port 53
+fn parse_nameserver(value: &str) -> Result<SocketAddr, String> {
+    SocketAddr::from_str(value)
+        .or_else(|_| IpAddr::from_str(value).map(|ip_address| SocketAddr::new(ip_address, 53)))
+        .map_err(|_| format!("'{}' is not an IP address with an optional port", value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_nameserver_with_port() {
+        assert_eq!(
+            parse_nameserver("127.0.0.1:5353"),
+            Ok(SocketAddr::from_str("127.0.0.1:5353").unwrap())
+        );
+        assert_eq!(
+            parse_nameserver("[2001:db8::1]:5353"),
+            Ok(SocketAddr::from_str("[2001:db8::1]:5353").unwrap())
+        );
+    }
+
+    #[test]
+    fn it_should_parse_nameserver_with_default_port() {
+        assert_eq!(
+            parse_nameserver("2001:db8::1"),
+            Ok(SocketAddr::from_str("[2001:db8::1]:53").unwrap())
+        );
+    }
+
+    #[test]
+    fn it_should_reject_invalid_nameserver() {
+        assert!(parse_nameserver("dns.example.com").is_err());
+    }
+}
@@ -1,17 +1,75 @@
-use crate::dns::core::dns_resolver::{ARecordQuery, DnsResolver, MxRecordQuery, TxtRecordQuery};
-use crate::dns::domain::{ARecord, MxRecord, TxtRecord};
+use crate::dns::core::dns_resolver::{
+    ARecordQuery, AaaaRecordQuery, DnsResolver, MxRecordQuery, PtrRecordQuery, TxtRecordQuery,
+};
+use crate::dns::domain::{ARecord, AaaaRecord, DnsError, MxRecord, PtrRecord, TxtRecord};
+use async_trait::async_trait;
+use domain::base::iana::Rcode;
 use domain::base::{Dname, Rtype};
-use domain::rdata::{AllRecordData, Mx};
+use domain::rdata::{Aaaa, Mx, Ptr, Soa, Txt, A};
+use domain::resolv::stub::conf::{ResolvConf, ServerConf, Transport};
+use domain::resolv::stub::Answer;
 use domain::resolv::StubResolver;
-use std::error::Error;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
-use std::thread;
+use std::time::Duration;
 
-pub struct DomainDnsResolver {}
+/// Resolves queries with the system configuration, sharing one stub resolver for all queries
+pub struct DomainDnsResolver {
+    stub: StubResolver,
+}
+
+/// Overrides of the system resolver configuration
+#[derive(Debug, Default)]
+pub struct DnsResolverOptions {
+    /// Nameservers to query instead of the ones in `/etc/resolv.conf`
+    pub nameservers: Vec<SocketAddr>,
+
+    /// How long to wait for an answer of a nameserver
+    pub timeout: Option<Duration>,
+
+    /// How often to retry a query that got no answer
+    pub retries: Option<usize>,
+
+    /// Query over TCP instead of UDP
+    pub tcp: bool,
+}
 
 impl DomainDnsResolver {
     pub fn new() -> Self {
-        DomainDnsResolver {}
+        DomainDnsResolver {
+            stub: StubResolver::new(),
+        }
+    }
+
+    /// Resolves queries with the system configuration, overridden by the options
+    pub fn with_options(options: &DnsResolverOptions) -> Self {
+        DomainDnsResolver {
+            stub: StubResolver::from_conf(resolv_conf(options)),
+        }
+    }
+
+    /// Query a domain name and map failed answers to a `DnsError`
+    async fn query(&self, domain_name: &str, rtype: Rtype) -> Result<Answer, DnsError> {
+        let name = Dname::<Vec<_>>::from_str(domain_name)
+            .map_err(|_| DnsError::InvalidName(domain_name.to_owned()))?;
+
+        let answer = self.stub.query((name, rtype)).await.map_err(|err| {
+            log::debug!("Query for '{}' failed: {}", domain_name, err);
+            match err.kind() {
+                io::ErrorKind::TimedOut => DnsError::Timeout(domain_name.to_owned()),
+                _ => DnsError::ServFail(domain_name.to_owned()),
+            }
+        })?;
+
+        match answer.header().rcode() {
+            Rcode::NoError => Ok(answer),
+            Rcode::NXDomain => Err(DnsError::NxDomain {
+                name: domain_name.to_owned(),
+                negative_ttl: negative_ttl(&answer),
+            }),
+            _ => Err(DnsError::ServFail(domain_name.to_owned())),
+        }
     }
 }
 
@@ -21,100 +79,258 @@ impl Default for DomainDnsResolver {
     }
 }
 
-#[allow(clippy::needless_return)]
+#[async_trait]
 impl DnsResolver for DomainDnsResolver {
-    fn query_a(&mut self, query: &ARecordQuery) -> Result<ARecord, Box<dyn Error>> {
-        let domain_name = Dname::<Vec<_>>::from_str(&query.domain_name).unwrap();
+    async fn query_a(&self, query: &ARecordQuery) -> Result<ARecord, DnsError> {
+        let domain_name = &query.domain_name;
         log::trace!(
             "Request dns question of type 'a record' for '{}'",
             domain_name
         );
 
-        let res = thread::spawn(|| {
-            return StubResolver::run(move |stub| async move {
-                let res = stub.lookup_host(domain_name).await;
-
-                match res {
-                    Ok(answer) => {
-                        let ip_addresses = answer.iter().collect::<Vec<_>>();
-                        log::debug!("Got dns answer with {} records", ip_addresses.len());
+        let answer = self.query(domain_name, Rtype::A).await?;
+        let records = answer
+            .answer()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?
+            .limit_to::<A>()
+            .map(|record| record.map(|record| (record.data().addr(), record.ttl().as_secs())))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?;
 
-                        Ok(ip_addresses)
-                    }
-                    Err(err) => Err(Box::new(err)),
-                }
+        log::debug!("Got dns answer with {} records", records.len());
+        if records.is_empty() {
+            return Err(DnsError::NoData {
+                name: domain_name.to_owned(),
+                negative_ttl: negative_ttl(&answer),
             });
-        })
-        .join()
-        .expect("Thread panicked");
+        }
+        let (ip_addresses, ttl) = with_min_ttl(records);
+        Ok(ARecord { ip_addresses, ttl })
+    }
 
-        match res {
-            Ok(answer) => Ok(ARecord {
-                ip_addresses: answer,
-            }),
-            Err(err) => Err(Box::new(err)),
+    async fn query_aaaa(&self, query: &AaaaRecordQuery) -> Result<AaaaRecord, DnsError> {
+        let domain_name = &query.domain_name;
+        log::trace!(
+            "Request dns question of type 'aaaa record' for '{}'",
+            domain_name
+        );
+
+        let answer = self.query(domain_name, Rtype::Aaaa).await?;
+        let records = answer
+            .answer()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?
+            .limit_to::<Aaaa>()
+            .map(|record| record.map(|record| (record.data().addr(), record.ttl().as_secs())))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?;
+
+        log::debug!("Got dns answer with {} records", records.len());
+        if records.is_empty() {
+            return Err(DnsError::NoData {
+                name: domain_name.to_owned(),
+                negative_ttl: negative_ttl(&answer),
+            });
         }
+        let (ip_addresses, ttl) = with_min_ttl(records);
+        Ok(AaaaRecord { ip_addresses, ttl })
     }
 
-    fn query_txt(&mut self, command: &TxtRecordQuery) -> Result<TxtRecord, Box<dyn Error>> {
-        let domain_name = Dname::<Vec<_>>::from_str(&command.domain_name).unwrap();
+    async fn query_txt(&self, command: &TxtRecordQuery) -> Result<TxtRecord, DnsError> {
+        let domain_name = &command.domain_name;
         log::trace!(
             "Request dns question of type 'txt record' for '{}'",
             domain_name
         );
 
-        let res = thread::spawn(|| {
-            return StubResolver::run(move |stub| async move {
-                stub.query((domain_name, Rtype::Txt)).await
+        let answer = self.query(domain_name, Rtype::Txt).await?;
+        let records = answer
+            .answer()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?
+            .limit_to::<Txt<_>>()
+            .map(|record| {
+                record.map(|record| {
+                    let character_strings = record
+                        .data()
+                        .iter()
+                        .map(|character_string| {
+                            String::from_utf8_lossy(character_string).into_owned()
+                        })
+                        .collect::<Vec<_>>();
+                    (character_strings, record.ttl().as_secs())
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?;
+
+        log::debug!("Got dns answer with {} records", records.len());
+        if records.is_empty() {
+            return Err(DnsError::NoData {
+                name: domain_name.to_owned(),
+                negative_ttl: negative_ttl(&answer),
             });
-        })
-        .join()
-        .expect("Thread panicked");
-
-        match res {
-            Ok(answer) => {
-                let records = answer
-                    .answer()
-                    .unwrap()
-                    .limit_to::<AllRecordData<_, _>>()
-                    .map(|record| record.unwrap().data().to_string().replace("\\32", " "))
-                    .collect::<Vec<String>>();
-
-                log::debug!("Got dns answer with {} records", records.len());
-                Ok(TxtRecord { records })
-            }
-            Err(err) => Err(Box::new(err)),
         }
+        let (records, ttl) = with_min_ttl(records);
+        Ok(TxtRecord { records, ttl })
     }
 
-    fn query_mx(&mut self, query: &MxRecordQuery) -> Result<MxRecord, Box<dyn Error>> {
-        let domain_name = Dname::<Vec<_>>::from_str(&query.domain_name).unwrap();
+    async fn query_mx(&self, query: &MxRecordQuery) -> Result<MxRecord, DnsError> {
+        let domain_name = &query.domain_name;
         log::trace!(
             "Request dns question of type 'mx record' for '{}'",
             domain_name
         );
 
-        let res = thread::spawn(|| {
-            return StubResolver::run(move |stub| async move {
-                stub.query((domain_name, Rtype::Mx)).await
+        let answer = self.query(domain_name, Rtype::Mx).await?;
+        let records = answer
+            .answer()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?
+            .limit_to::<Mx<_>>()
+            .map(|record| {
+                record.map(|record| (record.data().exchange().to_string(), record.ttl().as_secs()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?;
+
+        log::debug!("Got dns answer with {} records", records.len());
+        if records.is_empty() {
+            return Err(DnsError::NoData {
+                name: domain_name.to_owned(),
+                negative_ttl: negative_ttl(&answer),
             });
-        })
-        .join()
-        .expect("Thread panicked");
-
-        match res {
-            Ok(answer) => {
-                let exchanges = answer
-                    .answer()
-                    .unwrap()
-                    .limit_to::<Mx<_>>()
-                    .map(|record| record.unwrap().data().exchange().to_string())
-                    .collect::<Vec<String>>();
-
-                log::debug!("Got dns answer with {} records", exchanges.len());
-                Ok(MxRecord { exchanges })
-            }
-            Err(err) => Err(Box::new(err)),
         }
+        let (exchanges, ttl) = with_min_ttl(records);
+        Ok(MxRecord { exchanges, ttl })
+    }
+
+    async fn query_ptr(&self, query: &PtrRecordQuery) -> Result<PtrRecord, DnsError> {
+        let domain_name = reverse_name(&query.ip_address);
+        log::trace!(
+            "Request dns question of type 'ptr record' for '{}'",
+            query.ip_address
+        );
+
+        let answer = self.query(&domain_name, Rtype::Ptr).await?;
+        let records = answer
+            .answer()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?
+            .limit_to::<Ptr<_>>()
+            .map(|record| {
+                record.map(|record| (record.data().ptrdname().to_string(), record.ttl().as_secs()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DnsError::ServFail(domain_name.to_owned()))?;
+
+        log::debug!("Got dns answer with {} records", records.len());
+        if records.is_empty() {
+            return Err(DnsError::NoData {
+                name: domain_name,
+                negative_ttl: negative_ttl(&answer),
+            });
+        }
+        let (names, ttl) = with_min_ttl(records);
+        Ok(PtrRecord { names, ttl })
+    }
+}
+
+/// The system resolver configuration, overridden by the options
+fn resolv_conf(options: &DnsResolverOptions) -> ResolvConf {
+    let mut conf = ResolvConf::default();
+    if !options.nameservers.is_empty() {
+        conf.servers = options
+            .nameservers
+            .iter()
+            .flat_map(|addr| {
+                [
+                    ServerConf::new(*addr, Transport::Udp),
+                    ServerConf::new(*addr, Transport::Tcp),
+                ]
+            })
+            .collect();
+    }
+    if let Some(timeout) = options.timeout {
+        conf.options.timeout = timeout;
+    }
+    if let Some(retries) = options.retries {
+        conf.options.attempts = retries + 1;
+    }
+    conf.options.use_vc = options.tcp;
+    conf.finalize();
+    conf
+}
+
+/// Split the values of records from their TTLs, keeping the lowest TTL
+fn with_min_ttl<T>(records: Vec<(T, u32)>) -> (Vec<T>, u32) {
+    let ttl = records.iter().map(|(_, ttl)| *ttl).min().unwrap_or(0);
+    (records.into_iter().map(|(value, _)| value).collect(), ttl)
+}
+
+/// The TTL of a negative answer, the lower of the SOA record TTL and its minimum field
+/// (RFC 2308 section 5), none if the answer has no SOA record and must not be cached
+fn negative_ttl(answer: &Answer) -> Option<u32> {
+    let soa = answer.authority().ok()?.limit_to::<Soa<_>>().next()?.ok()?;
+    Some(soa.ttl().as_secs().min(soa.data().minimum().as_secs()))
+}
+
+/// The domain name of the PTR record of an IP address (RFC 1035 section 3.5, RFC 3596 section 2.5)
+pub(crate) fn reverse_name(ip_address: &IpAddr) -> String {
+    match ip_address {
+        IpAddr::V4(ip) => {
+            let [a, b, c, d] = ip.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+        }
+        IpAddr::V6(ip) => {
+            let nibbles = ip
+                .octets()
+                .iter()
+                .rev()
+                .map(|octet| format!("{:x}.{:x}", octet & 0xf, octet >> 4))
+                .collect::<Vec<_>>();
+            format!("{}.ip6.arpa", nibbles.join("."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_override_system_configuration() {
+        // Arrange
+        let options = DnsResolverOptions {
+            nameservers: vec![SocketAddr::from_str("127.0.0.1:5353").unwrap()],
+            timeout: Some(Duration::from_secs(5)),
+            retries: Some(0),
+            tcp: true,
+        };
+
+        // Act
+        let conf = resolv_conf(&options);
+
+        // Assert
+        assert_eq!(
+            conf.servers
+                .iter()
+                .map(|server| (server.addr.to_string(), server.transport))
+                .collect::<Vec<_>>(),
+            vec![
+                ("127.0.0.1:5353".to_owned(), Transport::Udp),
+                ("127.0.0.1:5353".to_owned(), Transport::Tcp)
+            ]
+        );
+        assert!(conf
+            .servers
+            .iter()
+            .all(|server| server.request_timeout == Duration::from_secs(5)));
+        assert_eq!(conf.options.attempts, 1);
+        assert!(conf.options.use_vc);
+    }
+
+    #[test]
+    fn it_should_reverse_ip_address() {
+        assert_eq!(
+            reverse_name(&IpAddr::from_str("192.0.2.1").unwrap()),
+            "1.2.0.192.in-addr.arpa"
+        );
     }
 }
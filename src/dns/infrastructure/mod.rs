@@ -1 +1,5 @@
+pub mod caching_dns_resolver;
+pub mod cli;
 pub mod dns_resolver;
+pub mod session_dns_resolver;
+pub mod zone_file_dns_resolver;
@@ -0,0 +1,301 @@
+use crate::dns::core::dns_resolver::{
+    ARecordQuery, AaaaRecordQuery, DnsResolver, MxRecordQuery, PtrRecordQuery, TxtRecordQuery,
+};
+use crate::dns::domain::{ARecord, AaaaRecord, DnsError, MxRecord, PtrRecord, TxtRecord};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A query with the answer it got, as stored in a session file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum DnsExchange {
+    A {
+        name: String,
+        answer: Result<ARecord, DnsError>,
+    },
+    Aaaa {
+        name: String,
+        answer: Result<AaaaRecord, DnsError>,
+    },
+    Txt {
+        name: String,
+        answer: Result<TxtRecord, DnsError>,
+    },
+    Mx {
+        name: String,
+        answer: Result<MxRecord, DnsError>,
+    },
+    Ptr {
+        ip_address: IpAddr,
+        answer: Result<PtrRecord, DnsError>,
+    },
+}
+
+/// Writes every query of another resolver with its answer to a session file,
+/// once the resolver is dropped
+pub struct RecordingDnsResolver {
+    inner: Box<dyn DnsResolver>,
+    path: PathBuf,
+    exchanges: Mutex<Vec<DnsExchange>>,
+}
+
+impl RecordingDnsResolver {
+    /// Record the queries of the resolver, failing early if the session file cannot be created
+    pub fn create(path: &Path, inner: Box<dyn DnsResolver>) -> Result<Self, Box<dyn Error>> {
+        File::create(path)
+            .map_err(|err| format!("Cannot create session file '{}': {}", path.display(), err))?;
+        Ok(RecordingDnsResolver {
+            inner,
+            path: path.to_owned(),
+            exchanges: Mutex::default(),
+        })
+    }
+
+    fn record(&self, exchange: DnsExchange) {
+        self.exchanges.lock().unwrap().push(exchange);
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        let file = BufWriter::new(File::create(&self.path)?);
+        serde_json::to_writer_pretty(file, &*self.exchanges.lock().unwrap())?;
+        Ok(())
+    }
+}
+
+impl Drop for RecordingDnsResolver {
+    fn drop(&mut self) {
+        match self.save() {
+            Ok(()) => log::debug!("DNS session written to '{}'", self.path.display()),
+            Err(err) => log::error!(
+                "Cannot write session file '{}': {}",
+                self.path.display(),
+                err
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl DnsResolver for RecordingDnsResolver {
+    async fn query_a(&self, query: &ARecordQuery) -> Result<ARecord, DnsError> {
+        let answer = self.inner.query_a(query).await;
+        self.record(DnsExchange::A {
+            name: query.domain_name.to_owned(),
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn query_aaaa(&self, query: &AaaaRecordQuery) -> Result<AaaaRecord, DnsError> {
+        let answer = self.inner.query_aaaa(query).await;
+        self.record(DnsExchange::Aaaa {
+            name: query.domain_name.to_owned(),
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn query_txt(&self, query: &TxtRecordQuery) -> Result<TxtRecord, DnsError> {
+        let answer = self.inner.query_txt(query).await;
+        self.record(DnsExchange::Txt {
+            name: query.domain_name.to_owned(),
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn query_mx(&self, query: &MxRecordQuery) -> Result<MxRecord, DnsError> {
+        let answer = self.inner.query_mx(query).await;
+        self.record(DnsExchange::Mx {
+            name: query.domain_name.to_owned(),
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn query_ptr(&self, query: &PtrRecordQuery) -> Result<PtrRecord, DnsError> {
+        let answer = self.inner.query_ptr(query).await;
+        self.record(DnsExchange::Ptr {
+            ip_address: query.ip_address,
+            answer: answer.clone(),
+        });
+        answer
+    }
+}
+
+/// Answers queries with the answers of a recorded session, queries that were not
+/// recorded fail with a server failure
+pub struct ReplayDnsResolver {
+    exchanges: Vec<DnsExchange>,
+}
+
+impl ReplayDnsResolver {
+    pub fn new(exchanges: Vec<DnsExchange>) -> Self {
+        ReplayDnsResolver { exchanges }
+    }
+
+    /// Load the answers of a session file written by `RecordingDnsResolver`
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)
+            .map_err(|err| format!("Cannot open session file '{}': {}", path.display(), err))?;
+        let exchanges = serde_json::from_reader(BufReader::new(file))
+            .map_err(|err| format!("Invalid session file '{}': {}", path.display(), err))?;
+        Ok(ReplayDnsResolver::new(exchanges))
+    }
+
+    /// The answer of the first recorded exchange selected by `answer`
+    fn replay<T>(
+        &self,
+        name: &str,
+        answer: impl Fn(&DnsExchange) -> Option<&Result<T, DnsError>>,
+    ) -> Result<T, DnsError>
+    where
+        T: Clone,
+    {
+        self.exchanges
+            .iter()
+            .find_map(answer)
+            .cloned()
+            .unwrap_or_else(|| {
+                log::warn!("No answer for '{}' in the recorded session", name);
+                Err(DnsError::ServFail(name.to_owned()))
+            })
+    }
+}
+
+#[async_trait]
+impl DnsResolver for ReplayDnsResolver {
+    async fn query_a(&self, query: &ARecordQuery) -> Result<ARecord, DnsError> {
+        self.replay(&query.domain_name, |exchange| match exchange {
+            DnsExchange::A { name, answer } if same_name(name, &query.domain_name) => Some(answer),
+            _ => None,
+        })
+    }
+
+    async fn query_aaaa(&self, query: &AaaaRecordQuery) -> Result<AaaaRecord, DnsError> {
+        self.replay(&query.domain_name, |exchange| match exchange {
+            DnsExchange::Aaaa { name, answer } if same_name(name, &query.domain_name) => {
+                Some(answer)
+            }
+            _ => None,
+        })
+    }
+
+    async fn query_txt(&self, query: &TxtRecordQuery) -> Result<TxtRecord, DnsError> {
+        self.replay(&query.domain_name, |exchange| match exchange {
+            DnsExchange::Txt { name, answer } if same_name(name, &query.domain_name) => {
+                Some(answer)
+            }
+            _ => None,
+        })
+    }
+
+    async fn query_mx(&self, query: &MxRecordQuery) -> Result<MxRecord, DnsError> {
+        self.replay(&query.domain_name, |exchange| match exchange {
+            DnsExchange::Mx { name, answer } if same_name(name, &query.domain_name) => Some(answer),
+            _ => None,
+        })
+    }
+
+    async fn query_ptr(&self, query: &PtrRecordQuery) -> Result<PtrRecord, DnsError> {
+        self.replay(&query.ip_address.to_string(), |exchange| match exchange {
+            DnsExchange::Ptr { ip_address, answer } if *ip_address == query.ip_address => {
+                Some(answer)
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Domain names are compared case-insensitively and without the trailing dot
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim_end_matches('.')
+        .eq_ignore_ascii_case(b.trim_end_matches('.'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::net::Ipv4Addr;
+    use std::str::FromStr;
+
+    use crate::dns::core::dns_resolver::MockDnsResolver;
+
+    #[tokio::test]
+    async fn it_should_replay_recorded_session() {
+        // Arrange
+        let path = std::env::temp_dir().join("det-it_should_replay_recorded_session.json");
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().return_once(|_| {
+            Ok(TxtRecord {
+                records: vec![vec!["v=spf1 a -all".to_owned()]],
+                ttl: 3600,
+            })
+        });
+        dns_resolver.expect_query_a().return_once(|query| {
+            Err(DnsError::NxDomain {
+                name: query.domain_name.to_owned(),
+                negative_ttl: Some(300),
+            })
+        });
+        let recording_dns_resolver =
+            RecordingDnsResolver::create(&path, Box::new(dns_resolver)).unwrap();
+        let txt_query = TxtRecordQuery {
+            domain_name: "example.com".to_owned(),
+        };
+        let a_query = ARecordQuery {
+            domain_name: "example.com".to_owned(),
+        };
+        recording_dns_resolver.query_txt(&txt_query).await.unwrap();
+        let _ = recording_dns_resolver.query_a(&a_query).await;
+        drop(recording_dns_resolver);
+
+        // Act
+        let replay_dns_resolver = ReplayDnsResolver::load(&path).unwrap();
+        let txt_record = replay_dns_resolver.query_txt(&txt_query).await;
+        let a_record = replay_dns_resolver.query_a(&a_query).await;
+        std::fs::remove_file(&path).unwrap();
+
+        // Assert
+        assert_eq!(txt_record.unwrap().texts(), vec!["v=spf1 a -all"]);
+        assert_eq!(
+            a_record.err(),
+            Some(DnsError::NxDomain {
+                name: "example.com".to_owned(),
+                negative_ttl: Some(300),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_query_missing_from_session() {
+        // Arrange
+        let replay_dns_resolver = ReplayDnsResolver::new(vec![DnsExchange::A {
+            name: "example.com".to_owned(),
+            answer: Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("192.0.2.1").unwrap()],
+                ttl: 3600,
+            }),
+        }]);
+
+        // Act
+        let result = replay_dns_resolver
+            .query_a(&ARecordQuery {
+                domain_name: "mail.example.com".to_owned(),
+            })
+            .await;
+
+        // Assert
+        assert_eq!(
+            result.err(),
+            Some(DnsError::ServFail("mail.example.com".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use crate::dns::core::dns_resolver::{
+    ARecordQuery, AaaaRecordQuery, DnsResolver, MxRecordQuery, PtrRecordQuery, TxtRecordQuery,
+};
+use crate::dns::domain::{ARecord, AaaaRecord, DnsError, MxRecord, PtrRecord, TxtRecord};
+use crate::dns::infrastructure::dns_resolver::reverse_name;
+use async_trait::async_trait;
+use domain::base::Dname;
+use domain::rdata::ZoneRecordData;
+use domain::zonefile::inplace::{Entry, Zonefile};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+/// Answers queries from the records of a zone file (RFC 1035 section 5) without any network
+/// lookups, names without records in the file do not exist
+pub struct ZoneFileDnsResolver {
+    /// Records by owner name
+    records: HashMap<String, Vec<ZoneRecord>>,
+
+    /// TTL of negative answers, taken from the SOA record if the file has one
+    negative_ttl: Option<u32>,
+}
+
+struct ZoneRecord {
+    ttl: u32,
+    data: ZoneData,
+}
+
+enum ZoneData {
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    Txt(Vec<String>),
+    Mx(String),
+    Ptr(String),
+    Other,
+}
+
+impl ZoneFileDnsResolver {
+    /// Load the records of a zone file, relative names without an `$ORIGIN` are relative to the root
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut file = File::open(path)
+            .map_err(|err| format!("Cannot open zone file '{}': {}", path.display(), err))?;
+        let mut zonefile = Zonefile::load(&mut file)?;
+        zonefile.set_origin(Dname::root_bytes());
+        Self::parse(zonefile)
+    }
+
+    fn parse(zonefile: Zonefile) -> Result<Self, Box<dyn Error>> {
+        let mut resolver = ZoneFileDnsResolver {
+            records: HashMap::new(),
+            negative_ttl: None,
+        };
+
+        for entry in zonefile {
+            let record = match entry? {
+                Entry::Record(record) => record,
+                Entry::Include { path, .. } => {
+                    return Err(format!("$INCLUDE of '{}' is not supported", path).into());
+                }
+            };
+
+            let ttl = record.ttl().as_secs();
+            let data = match record.data() {
+                ZoneRecordData::A(a) => ZoneData::A(a.addr()),
+                ZoneRecordData::Aaaa(aaaa) => ZoneData::Aaaa(aaaa.addr()),
+                ZoneRecordData::Txt(txt) => ZoneData::Txt(
+                    txt.iter()
+                        .map(|character_string| {
+                            String::from_utf8_lossy(character_string).into_owned()
+                        })
+                        .collect(),
+                ),
+                ZoneRecordData::Mx(mx) => ZoneData::Mx(name_key(&mx.exchange().to_string())),
+                ZoneRecordData::Ptr(ptr) => ZoneData::Ptr(name_key(&ptr.ptrdname().to_string())),
+                ZoneRecordData::Soa(soa) => {
+                    // RFC 2308 section 5
+                    resolver.negative_ttl = Some(ttl.min(soa.minimum().as_secs()));
+                    ZoneData::Other
+                }
+                _ => ZoneData::Other,
+            };
+
+            resolver
+                .records
+                .entry(name_key(&record.owner().to_string()))
+                .or_default()
+                .push(ZoneRecord { ttl, data });
+        }
+
+        Ok(resolver)
+    }
+
+    /// The values of the records of a name, mapped by `select` with their lowest TTL
+    fn query<T>(
+        &self,
+        domain_name: &str,
+        select: impl Fn(&ZoneData) -> Option<T>,
+    ) -> Result<(Vec<T>, u32), DnsError> {
+        log::trace!("Look up '{}' in the zone file", domain_name);
+        let Some(records) = self.records.get(&name_key(domain_name)) else {
+            return Err(DnsError::NxDomain {
+                name: domain_name.to_owned(),
+                negative_ttl: self.negative_ttl,
+            });
+        };
+
+        let (values, ttls): (Vec<_>, Vec<_>) = records
+            .iter()
+            .filter_map(|record| select(&record.data).map(|value| (value, record.ttl)))
+            .unzip();
+        match ttls.into_iter().min() {
+            Some(ttl) => Ok((values, ttl)),
+            None => Err(DnsError::NoData {
+                name: domain_name.to_owned(),
+                negative_ttl: self.negative_ttl,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl DnsResolver for ZoneFileDnsResolver {
+    async fn query_a(&self, query: &ARecordQuery) -> Result<ARecord, DnsError> {
+        let (ip_addresses, ttl) = self.query(&query.domain_name, |data| match data {
+            ZoneData::A(ip_address) => Some(*ip_address),
+            _ => None,
+        })?;
+        Ok(ARecord { ip_addresses, ttl })
+    }
+
+    async fn query_aaaa(&self, query: &AaaaRecordQuery) -> Result<AaaaRecord, DnsError> {
+        let (ip_addresses, ttl) = self.query(&query.domain_name, |data| match data {
+            ZoneData::Aaaa(ip_address) => Some(*ip_address),
+            _ => None,
+        })?;
+        Ok(AaaaRecord { ip_addresses, ttl })
+    }
+
+    async fn query_txt(&self, query: &TxtRecordQuery) -> Result<TxtRecord, DnsError> {
+        let (records, ttl) = self.query(&query.domain_name, |data| match data {
+            ZoneData::Txt(character_strings) => Some(character_strings.to_owned()),
+            _ => None,
+        })?;
+        Ok(TxtRecord { records, ttl })
+    }
+
+    async fn query_mx(&self, query: &MxRecordQuery) -> Result<MxRecord, DnsError> {
+        let (exchanges, ttl) = self.query(&query.domain_name, |data| match data {
+            ZoneData::Mx(exchange) => Some(exchange.to_owned()),
+            _ => None,
+        })?;
+        Ok(MxRecord { exchanges, ttl })
+    }
+
+    async fn query_ptr(&self, query: &PtrRecordQuery) -> Result<PtrRecord, DnsError> {
+        let (names, ttl) = self.query(&reverse_name(&query.ip_address), |data| match data {
+            ZoneData::Ptr(name) => Some(name.to_owned()),
+            _ => None,
+        })?;
+        Ok(PtrRecord { names, ttl })
+    }
+}
+
+/// Domain names are compared case-insensitively and without the trailing dot
+/// (names of `@` in the zone file are displayed with a leading dot)
+fn name_key(domain_name: &str) -> String {
+    domain_name.trim_matches('.').to_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    fn resolver() -> ZoneFileDnsResolver {
+        let mut zonefile = Zonefile::from(
+            "$ORIGIN example.com.\n\
+             $TTL 3600\n\
+             @ IN SOA ns.example.com. hostmaster.example.com. 1 7200 3600 1209600 300\n\
+             @ IN TXT \"v=spf1 \" \"mx -all\"\n\
+             @ IN MX 10 mail\n\
+             mail 600 IN A 192.0.2.1\n\
+             mail IN AAAA 2001:db8::1\n\
+             1.2.0.192.in-addr.arpa. IN PTR mail.example.com.\n",
+        );
+        zonefile.set_origin(Dname::root_bytes());
+        ZoneFileDnsResolver::parse(zonefile).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_should_answer_txt_query() {
+        // Arrange
+        let resolver = resolver();
+
+        // Act
+        let txt_record = resolver
+            .query_txt(&TxtRecordQuery {
+                domain_name: "Example.com.".to_owned(),
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(txt_record.texts(), vec!["v=spf1 mx -all"]);
+        assert_eq!(txt_record.ttl, 3600);
+    }
+
+    #[tokio::test]
+    async fn it_should_answer_mx_and_address_queries() {
+        // Arrange
+        let resolver = resolver();
+
+        // Act
+        let mx_record = resolver
+            .query_mx(&MxRecordQuery {
+                domain_name: "example.com".to_owned(),
+            })
+            .await
+            .unwrap();
+        let a_record = resolver
+            .query_a(&ARecordQuery {
+                domain_name: "mail.example.com".to_owned(),
+            })
+            .await
+            .unwrap();
+        let ptr_record = resolver
+            .query_ptr(&PtrRecordQuery {
+                ip_address: IpAddr::from_str("192.0.2.1").unwrap(),
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(mx_record.exchanges, vec!["mail.example.com"]);
+        assert_eq!(
+            a_record.ip_addresses,
+            vec![Ipv4Addr::from_str("192.0.2.1").unwrap()]
+        );
+        assert_eq!(a_record.ttl, 600);
+        assert_eq!(ptr_record.names, vec!["mail.example.com"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_answer_nodata_for_existing_name() {
+        // Arrange
+        let resolver = resolver();
+
+        // Act
+        let result = resolver
+            .query_txt(&TxtRecordQuery {
+                domain_name: "mail.example.com".to_owned(),
+            })
+            .await;
+
+        // Assert
+        assert_eq!(
+            result.err(),
+            Some(DnsError::NoData {
+                name: "mail.example.com".to_owned(),
+                negative_ttl: Some(300),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_answer_nxdomain_for_unknown_name() {
+        // Arrange
+        let resolver = resolver();
+
+        // Act
+        let result = resolver
+            .query_aaaa(&AaaaRecordQuery {
+                domain_name: "www.example.com".to_owned(),
+            })
+            .await;
+
+        // Assert
+        assert!(matches!(result, Err(DnsError::NxDomain { .. })));
+    }
+
+    #[test]
+    fn it_should_reject_include() {
+        let zonefile = Zonefile::from("$INCLUDE other.zone\n");
+
+        assert!(ZoneFileDnsResolver::parse(zonefile).is_err());
+    }
+}
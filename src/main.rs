@@ -2,6 +2,7 @@
 //!
 //! You can use `det` to check the following DNS records:
 //! - [Sender Policy Framework (SPF)](https://datatracker.ietf.org/doc/html/rfc7208)
+//! - [Domain-based Message Authentication, Reporting, and Conformance (DMARC)](https://datatracker.ietf.org/doc/html/rfc7489)
 //!
 //! # Usage
 //!
@@ -16,23 +17,96 @@
 //! det spf example.com --record "v=spf1 -all"
 //! ```
 //!
+//! or check only the syntax of a record without any DNS lookups
+//!
+//! ```bash
+//! det spf example.com --record "v=spf1 -all" --offline
+//! ```
+//!
 //! you can also get more details about the SPF record
 //!
 //! ```bash
 //! det spf example.com --detail
 //! ```
+//!
+//! or print the resolved record with all diagnostics as JSON for scripts
+//!
+//! ```bash
+//! det spf example.com --format json
+//! ```
+//!
+//! or evaluate whether an IP address is authorized to send mail for a domain
+//!
+//! ```bash
+//! det spf example.com --ip 203.0.113.7 --sender user@example.com
+//! ```
+//!
+//! Check the DMARC record for a domain, subdomains without a record fall back
+//! to the record of their organizational domain
+//!
+//! ```bash
+//! det dmarc example.com --detail
+//! ```
+//!
+//! organizational domains are found with a built-in snapshot of the Public Suffix List,
+//! a newer list can be loaded from a file
+//!
+//! ```bash
+//! det --public-suffix-list public_suffix_list.dat dmarc mail.example.co.uk
+//! ```
+//!
+//! the DNS queries can be sent to a specific nameserver instead of the system ones
+//!
+//! ```bash
+//! det spf example.com --nameserver 127.0.0.1:5353 --timeout 5 --tcp
+//! ```
+//!
+//! or answered from a zone file to check planned changes before publishing them
+//!
+//! ```bash
+//! det --zone-file staging.zone spf example.com
+//! ```
+//!
+//! the DNS answers of a check can be recorded and replayed later to reproduce it
+//!
+//! ```bash
+//! det --record-dns session.json spf example.com
+//! det --replay-dns session.json spf example.com
+//! ```
+//!
+//! # Exit codes
+//!
+//! - `0` no findings
+//! - `1` only warnings were found (ignored with `--fail-on error`)
+//! - `2` errors were found, or the command could not run
+//! - `3` a DNS query failed temporarily, the check is incomplete
+//!
+//! An evaluation with `det spf --ip` exits with
+//!
+//! - `0` pass, neutral or none
+//! - `1` softfail (ignored with `--fail-on error`)
+//! - `2` fail or permerror
+//! - `3` temperror
 
 use std::env;
 use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 use clap::{Parser, Subcommand};
 use clap_verbosity_flag::Verbosity;
 use common::cli::CliCommand;
 use simple_logger::SimpleLogger;
 
+use crate::common::outcome::Outcome;
+use crate::dmarc::infrastructure::cli::Dmarc;
+use crate::dns::domain::{use_public_suffix_list, PublicSuffixList};
+use crate::dns::infrastructure::cli::ResolverArgs;
 use crate::spf::infrastructure::cli::Spf;
 
 pub mod common;
+pub mod dmarc;
 pub mod dns;
 pub mod spf;
 
@@ -46,6 +120,14 @@ struct Cli {
     #[command(flatten)]
     verbose: Verbosity,
 
+    #[command(flatten)]
+    resolver: ResolverArgs,
+
+    /// Find organizational domains with a Public Suffix List file instead of the built-in one
+    /// (e.g. a newer copy of https://publicsuffix.org/list/public_suffix_list.dat)
+    #[arg(long, global = true, value_name = "PATH")]
+    public_suffix_list: Option<PathBuf>,
+
     #[clap(subcommand)]
     command: Commands,
 }
@@ -54,17 +136,47 @@ struct Cli {
 enum Commands {
     /// Sender Policy Framework (SPF) utility
     Spf(Spf),
+
+    /// Domain-based Message Authentication, Reporting, and Conformance (DMARC) utility
+    Dmarc(Dmarc),
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> ExitCode {
     let args = Cli::parse();
     SimpleLogger::new()
         .with_level(args.verbose.log_level_filter())
         .init()
         .unwrap();
 
+    match run(&args).await {
+        Ok(outcome) => ExitCode::from(outcome.exit_code()),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::from(Outcome::Errors.exit_code())
+        }
+    }
+}
+
+async fn run(args: &Cli) -> Result<Outcome, Box<dyn Error>> {
+    if let Some(path) = &args.public_suffix_list {
+        let text = fs::read_to_string(path).map_err(|err| {
+            format!(
+                "Cannot open public suffix list '{}': {}",
+                path.display(),
+                err
+            )
+        })?;
+        let list = PublicSuffixList::parse(&text);
+        if list.is_empty() {
+            return Err(format!("No rules in public suffix list '{}'", path.display()).into());
+        }
+        use_public_suffix_list(list)?;
+    }
+
+    let dns_resolver = args.resolver.dns_resolver()?;
     match &args.command {
-        Commands::Spf(spf) => spf.execute(),
+        Commands::Spf(spf) => spf.execute(dns_resolver.as_ref()).await,
+        Commands::Dmarc(dmarc) => dmarc.execute(dns_resolver.as_ref()).await,
     }
 }
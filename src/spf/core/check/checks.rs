@@ -1,5 +1,7 @@
+use crate::spf::core::resolver::use_case::{MAX_LOOKUP_COUNT, MAX_VOID_LOOKUP_COUNT};
 use crate::spf::domain::{
-    LabelSpan, Mechanism, Modifier, Severity, SyntaxError, Term, UnknownTerm,
+    ExpModifier, LabelSpan, LoopTerm, Mechanism, Modifier, MxMechanism, Severity, SyntaxError,
+    Term, UnknownTerm,
 };
 
 /// Records that are too long to fit in a single UDP packet
@@ -18,6 +20,7 @@ pub fn check_max_txt_length(rdata: &str) -> Result<(), Box<SyntaxError>> {
         let span = 0..rdata_short.len();
         Err(Box::new(
             SyntaxError::new("Max length exceeded")
+                .with_code("spf::max-length")
                 .with_src(rdata_short)
                 .with_src_labels(vec![LabelSpan::at(
                     span,
@@ -47,6 +50,7 @@ pub fn check_is_ascii(rdata: &str) -> Result<(), Box<SyntaxError>> {
 
         Err(Box::new(
             SyntaxError::new("Invalid character in SPF record")
+                .with_code("spf::non-ascii")
                 .with_src(rdata)
                 .with_src_labels(spans)
                 .with_help(format!("Remove {} from the SPF record.", non_ascii_chars)),
@@ -54,14 +58,44 @@ pub fn check_is_ascii(rdata: &str) -> Result<(), Box<SyntaxError>> {
     }
 }
 
+/// A domain must not publish more than one SPF record (RFC 7208 section 3.2)
+pub fn check_single_spf_record(records: &[String]) -> Result<(), Box<SyntaxError>> {
+    if records.len() <= 1 {
+        return Ok(());
+    }
+
+    let mut offset = 0;
+    let labels = records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let span = offset..offset + record.len();
+            offset += record.len() + 1;
+            LabelSpan::at(span, format!("SPF record {}", index + 1))
+        })
+        .collect::<Vec<_>>();
+
+    Err(Box::new(
+        SyntaxError::new(format!(
+            "Multiple SPF records found ({}), which results in a permerror",
+            records.len()
+        ))
+        .with_code("spf::multiple-records")
+        .with_src(records.join("\n"))
+        .with_src_labels(labels)
+        .with_help("Merge the SPF records into a single TXT record."),
+    ))
+}
+
 pub fn check_version(rdata: &str) -> Result<(), Box<SyntaxError>> {
     let version = rdata.split(' ').next().unwrap_or("");
     match version {
-        "v=spf1" => Ok(()),
+        version if version.eq_ignore_ascii_case("v=spf1") => Ok(()),
         s if !s.starts_with("v=") => {
             let span = rdata.find(' ').map(|pos| 0..pos).unwrap_or(0..rdata.len());
             Err(Box::new(
                 SyntaxError::new("Version must be defined")
+                    .with_code("spf::invalid-version")
                     .with_src(rdata)
                     .with_src_labels(vec![LabelSpan::at(span, "Version is missing")])
                     .with_help("Add 'v=spf1' to the beginning of the SPF record."),
@@ -71,6 +105,7 @@ pub fn check_version(rdata: &str) -> Result<(), Box<SyntaxError>> {
             let span = rdata.find(' ').map(|pos| 0..pos).unwrap_or(0..rdata.len());
             Err(Box::new(
                 SyntaxError::new("Invalid SPF version")
+                    .with_code("spf::invalid-version")
                     .with_src(rdata)
                     .with_src_labels(vec![LabelSpan::at(
                         span,
@@ -82,6 +117,69 @@ pub fn check_version(rdata: &str) -> Result<(), Box<SyntaxError>> {
     }
 }
 
+/// Every term must follow the SPF grammar (RFC 7208 section 12)
+pub fn check_syntax(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let errors = terms
+        .iter()
+        .flat_map(|term| {
+            syntax_errors(std::slice::from_ref(term))
+                .into_iter()
+                .map(move |err| (term, err))
+        })
+        .collect::<Vec<_>>();
+
+    match errors.as_slice() {
+        [] => Ok(()),
+        [(Term::Unknown(_), err)] => Err(Box::new((*err).clone().with_code("spf::invalid-syntax"))),
+        [(_, first_err), ..] => Err(Box::new(
+            SyntaxError::new("SPF record contains invalid terms")
+                .with_code("spf::invalid-syntax")
+                .with_src(raw_rdata)
+                .with_src_labels(errors.iter().flat_map(|(term, err)| match term {
+                    Term::Unknown(_) => err.src_labels.iter().flatten().cloned().collect(),
+                    // Invalid terms of nested records are labeled at the top-level term that leads to them
+                    _ => vec![LabelSpan::at(
+                        term.span(),
+                        format!("{} in the nested record", err.message),
+                    )],
+                }))
+                .with_help(first_err.help.to_owned().unwrap_or_default()),
+        )),
+    }
+}
+
+/// DNS queries of the terms must be answered, failures result in a temperror or permerror
+pub fn check_lookup_errors(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    // Failed lookups of nested records are labeled at the top-level term that leads to them
+    let errors = terms
+        .iter()
+        .flat_map(|term| {
+            failed_lookups(std::slice::from_ref(term))
+                .into_iter()
+                .filter_map(move |unknown| {
+                    let err = unknown.lookup_error.as_ref()?;
+                    let label = match term {
+                        Term::Unknown(_) => err.to_string(),
+                        _ => format!("{}: {}", unknown.raw_rdata, err),
+                    };
+                    Some(LabelSpan::at(term.span(), label))
+                })
+        })
+        .collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("DNS lookup failed")
+            .with_code("spf::lookup-failed")
+            .with_src(raw_rdata)
+            .with_src_labels(errors)
+            .with_help("Check that the name servers of the failing names answer the query."),
+    ))
+}
+
 pub fn check_has_unknown_term(terms: &[Term], raw_rdata: &str) -> Result<bool, Box<SyntaxError>> {
     let unknown_terms = unknown_terms(terms);
     if unknown_terms.is_empty() {
@@ -90,25 +188,16 @@ pub fn check_has_unknown_term(terms: &[Term], raw_rdata: &str) -> Result<bool, B
 
     Err(Box::new(
         SyntaxError::new("SPF record contains one or more unknown terms")
+            .with_code("spf::unknown-term")
             .with_severity(Severity::Warning)
             .with_src(raw_rdata)
             .with_src_labels(unknown_terms.iter().map(|unknown_term| {
-                let search_term = format!(" {}", &unknown_term.raw_rdata);
-                let span_begin = if let Some(index) = raw_rdata.find(search_term.as_str()) {
-                    index + 1
-                } else {
-                    0
-                };
-
-                let span_end = span_begin + unknown_term.raw_rdata.len();
-                let span_range = span_begin..span_end;
-
                 let label = if let Some(reason) = &unknown_term.reason {
                     reason.into()
                 } else {
                     format!("{} is an unknown term", unknown_term.raw_rdata)
                 };
-                LabelSpan::at(span_range, label)
+                LabelSpan::at(unknown_term.span.clone(), label)
             }))
             .with_help(if unknown_terms.len() == 1 {
                 format!("Remove the unknown term '{}'", &unknown_terms[0].raw_rdata)
@@ -125,6 +214,88 @@ pub fn check_has_unknown_term(terms: &[Term], raw_rdata: &str) -> Result<bool, B
     ))
 }
 
+/// The "ptr" mechanism SHOULD NOT be used (RFC 7208 section 5.5)
+pub fn check_ptr_mechanism(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let ptr_spans = terms
+        .iter()
+        .filter_map(|term| match term {
+            Term::Directive(d) if matches!(d.mechanism, Mechanism::Ptr(_)) => Some(d.span.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if ptr_spans.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("Use of the 'ptr' mechanism is not recommended")
+            .with_code("spf::ptr-mechanism")
+            .with_severity(Severity::Warning)
+            .with_src(raw_rdata)
+            .with_src_labels(ptr_spans.into_iter().map(|span| {
+                LabelSpan::at(
+                    span,
+                    "'ptr' is slow, unreliable and places a large burden on the DNS",
+                )
+            }))
+            .with_help("Replace 'ptr' with 'a', 'mx', 'ip4' or 'ip6' mechanisms."),
+    ))
+}
+
+/// The "exp" modifier must not appear more than once (RFC 7208 section 6)
+pub fn check_single_exp_modifier(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let exp_modifiers = exp_modifiers(terms);
+    if exp_modifiers.len() <= 1 {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("SPF record contains multiple 'exp' modifiers")
+            .with_code("spf::multiple-exp")
+            .with_src(raw_rdata)
+            .with_src_labels(exp_modifiers.iter().skip(1).map(|exp| {
+                LabelSpan::at(
+                    exp.span.clone(),
+                    "Duplicate 'exp' modifier results in a permerror",
+                )
+            }))
+            .with_help("Remove all but one 'exp' modifier."),
+    ))
+}
+
+/// The explanation of the "exp" modifier is only used if exactly one TXT record exists
+pub fn check_exp_target_exists(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let missing_targets = exp_modifiers(terms)
+        .into_iter()
+        .filter_map(|exp| match (&exp.domain_name, &exp.explain_string) {
+            (Some(domain_name), None) => Some((exp, domain_name)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let Some((_, first_domain_name)) = missing_targets.first() else {
+        return Ok(());
+    };
+
+    Err(Box::new(
+        SyntaxError::new("Explanation record of 'exp' modifier not found")
+            .with_code("spf::missing-explanation")
+            .with_severity(Severity::Warning)
+            .with_src(raw_rdata)
+            .with_src_labels(missing_targets.iter().map(|(exp, domain_name)| {
+                LabelSpan::at(
+                    exp.span.clone(),
+                    format!("No single TXT record found for '{}'", domain_name),
+                )
+            }))
+            .with_help(format!(
+                "Publish exactly one TXT record at '{}' or remove the 'exp' modifier.",
+                first_domain_name
+            )),
+    ))
+}
+
 pub fn check_all_is_rightmost(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
     let directive_all_index = terms.iter().position(|term| match term {
         Term::Directive(d) => matches!(d.mechanism, Mechanism::All(_)),
@@ -135,25 +306,23 @@ pub fn check_all_is_rightmost(terms: &[Term], raw_rdata: &str) -> Result<(), Box
         return Ok(());
     };
 
-    let ignored_terms = terms
+    let ignored_spans = terms
         .iter()
         .skip(is_last + 1)
         .filter_map(|t| match t {
-            Term::Directive(d) => Some(d.mechanism.to_string()),
+            Term::Directive(d) => Some(d.span.clone()),
             _ => None,
         })
-        .collect::<Vec<String>>();
+        .collect::<Vec<_>>();
 
-    if ignored_terms.is_empty() {
+    if ignored_spans.is_empty() {
         Ok(())
     } else {
-        let span = raw_rdata
-            .find(" all ")
-            .map(|pos| pos + 5..raw_rdata.len())
-            .unwrap_or(0..raw_rdata.len());
+        let span = ignored_spans[0].start..ignored_spans[ignored_spans.len() - 1].end;
 
         Err(Box::new(
             SyntaxError::new("Mechanisms after 'all' found")
+                .with_code("spf::all-not-rightmost")
                 .with_severity(Severity::Warning)
                 .with_src(raw_rdata)
                 .with_src_labels(vec![LabelSpan::at(span, "This will be ignored")])
@@ -167,16 +336,15 @@ pub fn check_no_redirect_with_all(terms: &[Term], raw_rdata: &str) -> Result<(),
         Term::Directive(d) => matches!(d.mechanism, Mechanism::All(_)),
         _ => false,
     });
-    let has_redirect_modifier = terms
-        .iter()
-        .any(|term| matches!(term, Term::Modifier(Modifier::Redirect(_))));
+    let redirect_modifier = terms.iter().find_map(|term| match term {
+        Term::Modifier(Modifier::Redirect(redirect)) => Some(redirect),
+        _ => None,
+    });
 
-    if has_all_directive && has_redirect_modifier {
-        let span = raw_rdata
-            .find(" redirect")
-            .map(|pos| pos + 1..pos + 9)
-            .unwrap_or(0..raw_rdata.len());
+    if let (true, Some(redirect)) = (has_all_directive, redirect_modifier) {
+        let span = redirect.span.start..redirect.span.start + "redirect".len();
         Err(Box::new(SyntaxError::new("SPF record contains 'all' directive and 'redirect' modifier")
+            .with_code("spf::redirect-with-all")
             .with_severity(Severity::Warning)
             .with_src(raw_rdata)
             .with_src_labels(vec![LabelSpan::at(
@@ -192,33 +360,36 @@ pub fn check_no_redirect_with_all(terms: &[Term], raw_rdata: &str) -> Result<(),
 
 pub fn check_redirect_is_rightmost(
     terms: &[Term],
-    _raw_rdata: &str,
+    raw_rdata: &str,
 ) -> Result<(), Box<SyntaxError>> {
     let last_index = terms.len().saturating_sub(1);
 
-    terms
+    let Some(index) = terms
         .iter()
         .position(|term| matches!(term, Term::Modifier(Modifier::Redirect(_))))
-        .or(Some(last_index))
-        .map(|index| {
-            if index == last_index {
-                Ok(())
-            } else {
-                Err(Box::new(SyntaxError::new("Redirect modifier not rightmost")
-                    .with_severity(Severity::Warning)
-                    .with_help("For clarity, any redirect modifier should appear as the very last term in a record."))
-                )
-            }
-        }).expect("position should not be empty")
+    else {
+        return Ok(());
+    };
+
+    if index == last_index {
+        Ok(())
+    } else {
+        Err(Box::new(SyntaxError::new("Redirect modifier not rightmost")
+            .with_code("spf::redirect-not-rightmost")
+            .with_severity(Severity::Warning)
+            .with_src(raw_rdata)
+            .with_src_labels(vec![LabelSpan::at(terms[index].span(), "Not the last term")])
+            .with_help("For clarity, any redirect modifier should appear as the very last term in a record."))
+        )
+    }
 }
 
 pub fn check_lookup_count(terms: &[Term], _raw_rdata: &str) -> Result<usize, Box<SyntaxError>> {
-    const MAX_LOOKUP_COUNT: usize = 10;
-
     let lookup_count = count_lookup(terms);
     if lookup_count > MAX_LOOKUP_COUNT {
         Err(Box::new(
             SyntaxError::new(format!("Max lookup count of {} exceeded", MAX_LOOKUP_COUNT))
+                .with_code("spf::max-lookups")
                 .with_help(
                 "Remove the excessive lookups (a, mx, ptr, include or exists) from the SPF record.",
             ),
@@ -228,7 +399,132 @@ pub fn check_lookup_count(terms: &[Term], _raw_rdata: &str) -> Result<usize, Box
     }
 }
 
-fn count_lookup(terms: &[Term]) -> usize {
+/// The "mx" mechanism must not return more than 10 names (RFC 7208 section 4.6.4)
+pub fn check_mx_host_count(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let labels = terms
+        .iter()
+        .filter_map(|term| match term {
+            Term::Directive(d) => match &d.mechanism {
+                Mechanism::Mx(mx) if mx.exceeds_host_limit() => Some(LabelSpan::at(
+                    d.span.clone(),
+                    format!(
+                        "{} MX hosts exceed the maximum of {}",
+                        mx.hosts.len(),
+                        MxMechanism::MAX_HOSTS
+                    ),
+                )),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if labels.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("Too many MX hosts for 'mx' mechanism")
+            .with_code("spf::max-mx-hosts")
+            .with_src(raw_rdata)
+            .with_src_labels(labels)
+            .with_help(format!(
+                "Reduce the MX records to at most {} or replace 'mx' with 'ip4' or 'ip6' mechanisms.",
+                MxMechanism::MAX_HOSTS
+            )),
+    ))
+}
+
+/// At most two DNS lookups may return no answer (RFC 7208 section 4.6.4)
+pub fn check_void_lookup_count(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    let void_lookup_count = count_void_lookup(terms);
+    if void_lookup_count <= MAX_VOID_LOOKUP_COUNT {
+        return Ok(());
+    }
+
+    let labels = terms.iter().filter_map(|term| match term {
+        Term::Directive(d) if d.mechanism.is_void_lookup() => {
+            Some(LabelSpan::at(d.span.clone(), "No answer"))
+        }
+        Term::Directive(d) => match &d.mechanism {
+            Mechanism::Include(i) if count_void_lookup(&i.terms) > 0 => Some(LabelSpan::at(
+                d.span.clone(),
+                format!(
+                    "{} lookups without answer in the included record",
+                    count_void_lookup(&i.terms)
+                ),
+            )),
+            _ => None,
+        },
+        Term::Modifier(Modifier::Redirect(r)) if count_void_lookup(&r.terms) > 0 => {
+            Some(LabelSpan::at(
+                r.span.clone(),
+                format!(
+                    "{} lookups without answer in the redirected record",
+                    count_void_lookup(&r.terms)
+                ),
+            ))
+        }
+        _ => None,
+    });
+
+    Err(Box::new(
+        SyntaxError::new(format!(
+            "Max void lookup count of {} exceeded",
+            MAX_VOID_LOOKUP_COUNT
+        ))
+        .with_code("spf::max-void-lookups")
+        .with_src(raw_rdata)
+        .with_src_labels(labels)
+        .with_help(format!(
+            "{} lookups returned no answer, which results in a permerror. Remove the mechanisms pointing to domain names without records.",
+            void_lookup_count
+        )),
+    ))
+}
+
+/// Includes and redirects must not point back to a domain that is already being evaluated
+pub fn check_include_loop(terms: &[Term], raw_rdata: &str) -> Result<(), Box<SyntaxError>> {
+    // Loops in nested records are labeled at the top-level term that leads to them
+    let labels = terms
+        .iter()
+        .flat_map(|term| {
+            include_loops(std::slice::from_ref(term))
+                .into_iter()
+                .map(|l| LabelSpan::at(term.span(), l.chain.join(" -> ")))
+        })
+        .collect::<Vec<_>>();
+
+    if labels.is_empty() {
+        return Ok(());
+    }
+
+    Err(Box::new(
+        SyntaxError::new("Include loop detected, which results in a permerror")
+            .with_code("spf::include-loop")
+            .with_src(raw_rdata)
+            .with_src_labels(labels)
+            .with_help("Remove the include or redirect that points back to a domain of the chain."),
+    ))
+}
+
+fn count_void_lookup(terms: &[Term]) -> usize {
+    terms
+        .iter()
+        .map(|term| match term {
+            Term::Directive(d) if d.mechanism.is_void_lookup() => 1,
+            Term::Directive(d) => match &d.mechanism {
+                Mechanism::Include(i) => count_void_lookup(&i.terms),
+                _ => 0,
+            },
+            Term::Modifier(Modifier::Redirect(r)) => count_void_lookup(&r.terms),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// The number of terms that cause DNS lookups, including the terms of nested records
+pub fn count_lookup(terms: &[Term]) -> usize {
     let current_count: usize = terms
         .iter()
         .map(|term| match term {
@@ -239,7 +535,9 @@ fn count_lookup(terms: &[Term]) -> usize {
                     1
                 }
             }
-            Term::Modifier(m) if m.need_lookup() => 1,
+            Term::Modifier(Modifier::Redirect(r)) => count_lookup(&r.terms) + 1,
+            // Terms are only unresolved if their lookup failed or exceeded the limit
+            Term::Unknown(u) if u.reason.is_some() => 1,
             _ => 0,
         })
         .sum();
@@ -247,6 +545,64 @@ fn count_lookup(terms: &[Term]) -> usize {
     current_count
 }
 
+/// The loops of the record and all included or redirected records
+fn include_loops(terms: &[Term]) -> Vec<&LoopTerm> {
+    terms
+        .iter()
+        .flat_map(|term| match term {
+            Term::Loop(l) => vec![l],
+            Term::Directive(d) => match &d.mechanism {
+                Mechanism::Include(i) => include_loops(&i.terms),
+                _ => vec![],
+            },
+            Term::Modifier(Modifier::Redirect(r)) => include_loops(&r.terms),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// The syntax errors of the record and all included or redirected records
+fn syntax_errors(terms: &[Term]) -> Vec<&SyntaxError> {
+    terms
+        .iter()
+        .flat_map(|term| match term {
+            Term::Unknown(u) => u.error.iter().collect(),
+            Term::Directive(d) => match &d.mechanism {
+                Mechanism::Include(i) => syntax_errors(&i.terms),
+                _ => vec![],
+            },
+            Term::Modifier(Modifier::Redirect(r)) => syntax_errors(&r.terms),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// The terms with failed lookups of the record and all included or redirected records
+pub fn failed_lookups(terms: &[Term]) -> Vec<&UnknownTerm> {
+    terms
+        .iter()
+        .flat_map(|term| match term {
+            Term::Unknown(u) if u.lookup_error.is_some() => vec![u],
+            Term::Directive(d) => match &d.mechanism {
+                Mechanism::Include(i) => failed_lookups(&i.terms),
+                _ => vec![],
+            },
+            Term::Modifier(Modifier::Redirect(r)) => failed_lookups(&r.terms),
+            _ => vec![],
+        })
+        .collect()
+}
+
+fn exp_modifiers(terms: &[Term]) -> Vec<&ExpModifier> {
+    terms
+        .iter()
+        .filter_map(|term| match term {
+            Term::Modifier(Modifier::Exp(exp)) => Some(exp),
+            _ => None,
+        })
+        .collect()
+}
+
 fn unknown_terms(terms: &[Term]) -> Vec<&UnknownTerm> {
     let unknown_terms = terms
         .iter()
@@ -260,7 +616,14 @@ fn unknown_terms(terms: &[Term]) -> Vec<&UnknownTerm> {
             //         None
             //     }
             // }
-            Term::Unknown(unknown) => Some(vec![unknown]),
+            // Invalid terms, failed lookups and exceeded lookups are reported by their own checks
+            Term::Unknown(unknown)
+                if unknown.error.is_none()
+                    && unknown.lookup_error.is_none()
+                    && !unknown.lookup_limit_exceeded =>
+            {
+                Some(vec![unknown])
+            }
             _ => None,
         })
         .flatten();
@@ -270,15 +633,17 @@ fn unknown_terms(terms: &[Term]) -> Vec<&UnknownTerm> {
 
 #[cfg(test)]
 mod test {
+    use crate::dns::domain::DnsError;
     use crate::spf::domain::{
-        AMechanism, AllMechanism, Directive, IncludeMechanism, RedirectModifier, Version,
+        AMechanism, AllMechanism, Directive, ExistsMechanism, IncludeMechanism, MxHost,
+        RedirectModifier, SpfRecord, Version,
     };
 
     use super::*;
 
     #[test]
     fn test_unknown_term_check_returns_err() {
-        let terms = vec![Term::new_unknown("", None)];
+        let terms = vec![Term::new_unknown("", 0..0, None)];
         let result = check_has_unknown_term(&terms, "");
 
         assert!(result.is_err());
@@ -301,6 +666,179 @@ mod test {
         assert!(result);
     }
 
+    #[test]
+    fn test_unknown_modifier_is_not_a_syntax_error() {
+        let raw_rdata = "v=spf1 a foo=bar -all";
+        let terms = SpfRecord::parse(raw_rdata).terms;
+
+        assert!(check_syntax(&terms, raw_rdata).is_ok());
+        assert!(check_has_unknown_term(&terms, raw_rdata).is_err());
+    }
+
+    #[test]
+    fn test_invalid_macro_returns_err_with_span() {
+        let raw_rdata = "v=spf1 a include:%{x}.example.com";
+        let terms = SpfRecord::parse(raw_rdata).terms;
+        let result = check_syntax(&terms, raw_rdata);
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 19);
+        assert_eq!(label.len(), 1);
+    }
+
+    #[test]
+    fn test_valid_macro_returns_ok() {
+        let raw_rdata = "v=spf1 include:%{ir}.%{v}._spf.%{d2}";
+        let terms = SpfRecord::parse(raw_rdata).terms;
+        let result = check_syntax(&terms, raw_rdata);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_multiple_invalid_terms_returns_all_labels() {
+        let raw_rdata = "v=spf1 ip4:192.0.2.300 a:%{x}.example.com foo -all";
+        let terms = SpfRecord::parse(raw_rdata).terms;
+        let result = check_syntax(&terms, raw_rdata);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.message, "SPF record contains invalid terms");
+        assert_eq!(err.src_labels.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_multiple_spf_records_returns_err() {
+        let records = vec!["v=spf1 -all".to_owned(), "v=spf1 a -all".to_owned()];
+        let result = check_single_spf_record(&records);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.src.as_deref(), Some("v=spf1 -all\nv=spf1 a -all"));
+        let label = err.src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 12);
+        assert_eq!(label.len(), 13);
+    }
+
+    #[test]
+    fn test_single_spf_record_returns_ok() {
+        let result = check_single_spf_record(&["v=spf1 -all".to_owned()]);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_lookup_count_includes_exists() {
+        let terms = vec![Term::with_a(), Term::with_exists()];
+        let result = check_lookup_count(&terms, "");
+
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[test]
+    fn test_mx_host_count_exceeded_returns_err() {
+        let raw_rdata = "v=spf1 a mx -all";
+        let mut terms = SpfRecord::parse(raw_rdata).terms;
+        if let Term::Directive(d) = &mut terms[1] {
+            if let Mechanism::Mx(mx) = &mut d.mechanism {
+                mx.hosts = (0..11)
+                    .map(|i| MxHost {
+                        name: format!("mx{}.example.com", i),
+                        ip_addresses: vec![],
+                    })
+                    .collect();
+            }
+        }
+        let result = check_mx_host_count(&terms, raw_rdata);
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 9);
+        assert_eq!(label.len(), 2);
+    }
+
+    #[test]
+    fn test_mx_host_count_within_limit_returns_ok() {
+        let terms = SpfRecord::parse("v=spf1 mx -all").terms;
+        let result = check_mx_host_count(&terms, "v=spf1 mx -all");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_void_lookup_count_exceeded_returns_err() {
+        let raw_rdata = "v=spf1 a:a.example.com a:b.example.com mx:c.example.com -all";
+        let terms = with_void_lookups(SpfRecord::parse(raw_rdata).terms);
+        let result = check_void_lookup_count(&terms, raw_rdata);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.severity, None);
+        let labels = err.src_labels.unwrap();
+        assert_eq!(labels.len(), 3);
+        assert_eq!(labels[2].offset(), 39);
+    }
+
+    #[test]
+    fn test_void_lookup_count_within_limit_returns_ok() {
+        let raw_rdata = "v=spf1 a:a.example.com a:b.example.com -all";
+        let terms = with_void_lookups(SpfRecord::parse(raw_rdata).terms);
+        let result = check_void_lookup_count(&terms, raw_rdata);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_ptr_mechanism_returns_warning() {
+        let raw_rdata = "v=spf1 a ptr -all";
+        let terms = SpfRecord::parse(raw_rdata).terms;
+        let result = check_ptr_mechanism(&terms, raw_rdata);
+
+        let err = result.unwrap_err();
+        assert_eq!(err.severity, Some(Severity::Warning));
+        let label = err.src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 9);
+        assert_eq!(label.len(), 3);
+    }
+
+    #[test]
+    fn test_without_ptr_mechanism_returns_ok() {
+        let terms = vec![Term::with_a(), Term::with_all()];
+        let result = check_ptr_mechanism(&terms, "");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_multiple_exp_modifiers_returns_err() {
+        let raw_rdata = "v=spf1 -all exp=a.example.com exp=b.example.com";
+        let terms = SpfRecord::parse(raw_rdata).terms;
+        let result = check_single_exp_modifier(&terms, raw_rdata);
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 30);
+    }
+
+    #[test]
+    fn test_single_exp_modifier_returns_ok() {
+        let terms = vec![Term::with_exp("exp=a.example.com", Some("Not allowed"))];
+        let result = check_single_exp_modifier(&terms, "");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_missing_exp_target_returns_warning() {
+        let terms = vec![Term::with_exp("exp=a.example.com", None)];
+        let result = check_exp_target_exists(&terms, "v=spf1 exp=a.example.com");
+
+        assert_eq!(result.unwrap_err().severity, Some(Severity::Warning));
+    }
+
+    #[test]
+    fn test_existing_exp_target_returns_ok() {
+        let terms = vec![Term::with_exp("exp=a.example.com", Some("Not allowed"))];
+        let result = check_exp_target_exists(&terms, "v=spf1 exp=a.example.com");
+
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_redirect_with_all_returns_err() {
         let terms = vec![Term::with_all(), Term::with_redirect()];
@@ -373,6 +911,17 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_redirect_is_not_rightmost_returns_err_with_span() {
+        let raw_rdata = "v=spf1 redirect=_spf.example.com a";
+        let terms = SpfRecord::parse(raw_rdata).terms;
+        let result = check_redirect_is_rightmost(&terms, raw_rdata);
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 7);
+        assert_eq!(label.len(), 25);
+    }
+
     #[test]
     fn test_redirect_is_rightmost_returns_ok() {
         let terms = vec![Term::with_a(), Term::with_redirect()];
@@ -381,6 +930,167 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_nested_include_loop_returns_err_with_chain() {
+        let raw_rdata = "v=spf1 include:_spf.example.com -all";
+        let mut terms = SpfRecord::parse(raw_rdata).terms;
+        if let Term::Directive(d) = &mut terms[0] {
+            if let Mechanism::Include(i) = &mut d.mechanism {
+                i.terms.push(Term::Loop(LoopTerm {
+                    raw_rdata: "include:example.com".to_string(),
+                    span: 7..26,
+                    chain: vec![
+                        "example.com".to_string(),
+                        "_spf.example.com".to_string(),
+                        "example.com".to_string(),
+                    ],
+                }));
+            }
+        }
+        let result = check_include_loop(&terms, raw_rdata);
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 7);
+        assert_eq!(label.len(), 24);
+        assert_eq!(
+            label.label(),
+            Some("example.com -> _spf.example.com -> example.com")
+        );
+    }
+
+    #[test]
+    fn test_without_include_loop_returns_ok() {
+        let terms = vec![Term::with_include_and_nested_unknown(), Term::with_all()];
+        let result = check_include_loop(&terms, "");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_lookup_error_returns_err_with_failing_name() {
+        let terms = vec![Term::Unknown(UnknownTerm {
+            raw_rdata: "a:mail.example.com".to_string(),
+            span: 7..25,
+            reason: None,
+            lookup_error: Some(DnsError::ServFail("mail.example.com".to_string())),
+            lookup_limit_exceeded: false,
+            error: None,
+        })];
+        let result = check_lookup_errors(&terms, "v=spf1 a:mail.example.com -all");
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(
+            label.label(),
+            Some("Server failure for 'mail.example.com' (SERVFAIL)")
+        );
+    }
+
+    #[test]
+    fn test_lookup_error_is_not_an_unknown_term() {
+        let terms = vec![Term::Unknown(UnknownTerm {
+            raw_rdata: "a:mail.example.com".to_string(),
+            span: 7..25,
+            reason: None,
+            lookup_error: Some(DnsError::Timeout("mail.example.com".to_string())),
+            lookup_limit_exceeded: false,
+            error: None,
+        })];
+        let result = check_has_unknown_term(&terms, "v=spf1 a:mail.example.com -all");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_nested_lookup_error_returns_err_at_include() {
+        let raw_rdata = "v=spf1 include:_spf.example.com -all";
+        let mut terms = SpfRecord::parse(raw_rdata).terms;
+        if let Term::Directive(d) = &mut terms[0] {
+            if let Mechanism::Include(i) = &mut d.mechanism {
+                i.terms.push(Term::Unknown(UnknownTerm {
+                    raw_rdata: "a:mail.example.com".to_string(),
+                    span: 7..25,
+                    reason: Some("Server failure for 'mail.example.com' (SERVFAIL)".to_string()),
+                    lookup_error: Some(DnsError::ServFail("mail.example.com".to_string())),
+                    lookup_limit_exceeded: false,
+                    error: None,
+                }));
+            }
+        }
+        let result = check_lookup_errors(&terms, raw_rdata);
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 7);
+        assert_eq!(
+            label.label(),
+            Some("a:mail.example.com: Server failure for 'mail.example.com' (SERVFAIL)")
+        );
+    }
+
+    #[test]
+    fn test_nested_invalid_term_returns_err_at_include() {
+        let raw_rdata = "v=spf1 include:_spf.example.com -all";
+        let mut terms = SpfRecord::parse(raw_rdata).terms;
+        if let Term::Directive(d) = &mut terms[0] {
+            if let Mechanism::Include(i) = &mut d.mechanism {
+                i.terms = SpfRecord::parse("v=spf1 a:%{z}.example.com -all").terms;
+            }
+        }
+        let result = check_syntax(&terms, raw_rdata);
+
+        let label = result.unwrap_err().src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 7);
+        assert_eq!(label.len(), 24);
+    }
+
+    #[test]
+    fn test_lookup_count_includes_failed_lookups() {
+        let terms = vec![
+            Term::with_a(),
+            Term::Unknown(UnknownTerm {
+                raw_rdata: "a:mail.example.com".to_string(),
+                span: 9..27,
+                reason: Some("Server failure for 'mail.example.com' (SERVFAIL)".to_string()),
+                lookup_error: Some(DnsError::ServFail("mail.example.com".to_string())),
+                lookup_limit_exceeded: false,
+                error: None,
+            }),
+        ];
+
+        assert_eq!(count_lookup(&terms), 2);
+    }
+
+    #[test]
+    fn test_exceeded_lookup_is_not_an_unknown_term() {
+        let terms = vec![Term::Unknown(UnknownTerm {
+            raw_rdata: "mx".to_string(),
+            span: 7..9,
+            reason: Some("Max lookup count of 10 exceeded".to_string()),
+            lookup_error: None,
+            lookup_limit_exceeded: true,
+            error: None,
+        })];
+        let result = check_has_unknown_term(&terms, "v=spf1 mx -all");
+
+        assert!(result.is_ok());
+    }
+
+    /// Mark the a and mx mechanisms as resolved without an answer
+    fn with_void_lookups(terms: Vec<Term>) -> Vec<Term> {
+        terms
+            .into_iter()
+            .map(|mut term| {
+                if let Term::Directive(d) = &mut term {
+                    match &mut d.mechanism {
+                        Mechanism::A(a) => a.domain_name = a.domain_spec.clone(),
+                        Mechanism::Mx(mx) => mx.domain_name = mx.domain_spec.clone(),
+                        _ => {}
+                    }
+                }
+                term
+            })
+            .collect()
+    }
+
     impl Term {
         fn with_include_and_nested_unknown() -> Self {
             Term::Directive(Directive {
@@ -390,25 +1100,53 @@ mod test {
                         version: "".to_string(),
                     },
                     domain_spec: "".to_string(),
-                    terms: vec![Term::new_unknown("", None)],
+                    terms: vec![Term::new_unknown("", 0..0, None)],
                     raw_rdata: "".to_string(),
                 }),
                 qualifier: None,
+                span: 0..0,
             })
         }
 
+        fn with_exists() -> Self {
+            Term::Directive(Directive {
+                mechanism: Mechanism::Exists(ExistsMechanism {
+                    raw_value: "".to_string(),
+                    domain_spec: "".to_string(),
+                    domain_name: None,
+                    ip_addresses: vec![],
+                }),
+                qualifier: None,
+                span: 0..0,
+            })
+        }
+
+        fn with_exp(raw_value: &str, explain_string: Option<&str>) -> Self {
+            let (_, domain_spec) = raw_value.split_once('=').unwrap();
+            Term::Modifier(Modifier::Exp(ExpModifier {
+                raw_value: raw_value.to_string(),
+                span: 0..0,
+                domain_spec: domain_spec.to_string(),
+                domain_name: Some(domain_spec.to_string()),
+                explain_string: explain_string.map(str::to_owned),
+                explanation: None,
+            }))
+        }
+
         fn with_all() -> Self {
             Term::Directive(Directive {
                 mechanism: Mechanism::All(AllMechanism {
                     raw_value: "".to_string(),
                 }),
                 qualifier: None,
+                span: 0..0,
             })
         }
 
         pub fn with_redirect() -> Self {
             Term::Modifier(Modifier::Redirect(RedirectModifier {
                 raw_value: "".to_string(),
+                span: 0..0,
                 version: Version {
                     version: "".to_string(),
                 },
@@ -422,10 +1160,14 @@ mod test {
             Term::Directive(Directive {
                 mechanism: Mechanism::A(AMechanism {
                     raw_value: "".to_string(),
+                    domain_spec: None,
+                    domain_name: None,
                     ip_addresses: vec![],
-                    subnet_mask: None,
+                    ip4_subnet_mask: None,
+                    ip6_subnet_mask: None,
                 }),
                 qualifier: None,
+                span: 0..0,
             })
         }
     }
@@ -0,0 +1,95 @@
+use serde::Serialize;
+
+use crate::spf::domain::{Severity, SpfError};
+
+/// An error or warning about the record
+#[derive(Serialize)]
+pub(super) struct Diagnostic {
+    pub(super) code: String,
+
+    /// "error", "warning" or "advice"
+    pub(super) severity: &'static str,
+    pub(super) message: String,
+    pub(super) help: Option<String>,
+
+    /// The text the labels point into (e.g. the record)
+    pub(super) source: Option<String>,
+    pub(super) labels: Vec<DiagnosticLabel>,
+}
+
+#[derive(Serialize)]
+pub(super) struct DiagnosticLabel {
+    pub(super) offset: usize,
+    pub(super) length: usize,
+    pub(super) label: Option<String>,
+}
+
+impl Diagnostic {
+    /// Returns true if the diagnostic makes the record invalid
+    pub(super) fn is_error(&self) -> bool {
+        self.severity == "error"
+    }
+
+    fn new(code: &str, message: String) -> Self {
+        Diagnostic {
+            code: code.to_owned(),
+            severity: "error",
+            message,
+            help: None,
+            source: None,
+            labels: vec![],
+        }
+    }
+}
+
+impl From<&SpfError> for Diagnostic {
+    fn from(error: &SpfError) -> Self {
+        match error {
+            SpfError::NoSpfRecordFound(message) => {
+                Diagnostic::new("spf::no-record", message.to_owned())
+            }
+            SpfError::MultipleSpfRecordsFound { domain_name, .. } => Diagnostic::new(
+                "spf::multiple-records",
+                format!("Multiple SPF records found for '{}'", domain_name),
+            ),
+            SpfError::IncludeLoop { chain } => Diagnostic::new(
+                "spf::include-loop",
+                format!("Include loop: {}", chain.join(" -> ")),
+            ),
+            SpfError::IncludeDepthExceeded { chain } => Diagnostic::new(
+                "spf::max-include-depth",
+                format!("Max include depth exceeded: {}", chain.join(" -> ")),
+            ),
+            SpfError::TempError(err) => Diagnostic::new("spf::temperror", err.to_string()),
+            SpfError::PermError(err) => Diagnostic::new("spf::permerror", err.to_string()),
+            SpfError::CheckFailed(err) => Diagnostic {
+                help: Some(err.description.to_owned()),
+                ..Diagnostic::new("spf::check-failed", err.summary.to_owned())
+            },
+            SpfError::SyntaxError(err) => Diagnostic {
+                code: err
+                    .code
+                    .to_owned()
+                    .unwrap_or_else(|| "spf::invalid-syntax".to_owned()),
+                severity: match error.severity() {
+                    Severity::Advice => "advice",
+                    Severity::Warning => "warning",
+                    Severity::Error => "error",
+                },
+                message: err.message.to_owned(),
+                help: err.help.to_owned(),
+                source: err.src.to_owned(),
+                labels: err
+                    .src_labels
+                    .iter()
+                    .flatten()
+                    .map(|label| DiagnosticLabel {
+                        offset: label.offset(),
+                        length: label.len(),
+                        label: label.label().map(str::to_owned),
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
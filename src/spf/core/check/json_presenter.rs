@@ -0,0 +1,390 @@
+use std::net::IpAddr;
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::common::presenter::Presenter;
+use crate::spf::core::check::diagnostic::Diagnostic;
+use crate::spf::core::check::use_case::SpfSummary;
+use crate::spf::domain::{Mechanism, Modifier, QualifierType, SpfError, Term};
+
+/// Version of the JSON document, increased on incompatible changes
+const SCHEMA_VERSION: u32 = 1;
+
+/// Prints the summary as a single JSON document
+pub struct SummarySpfJsonPresenter {
+    domain_name: String,
+}
+
+impl SummarySpfJsonPresenter {
+    pub fn new(domain_name: impl Into<String>) -> Self {
+        SummarySpfJsonPresenter {
+            domain_name: domain_name.into(),
+        }
+    }
+}
+
+impl Presenter<SpfSummary, SpfError> for SummarySpfJsonPresenter {
+    fn success(&mut self, data: &SpfSummary) {
+        print_json(&SummaryJson::from(data));
+    }
+
+    fn error(&mut self, error: &SpfError) {
+        print_json(&SummaryJson {
+            schema_version: SCHEMA_VERSION,
+            domain: self.domain_name.to_owned(),
+            record: None,
+            valid: false,
+            lookup_count: 0,
+            terms: vec![],
+            diagnostics: vec![Diagnostic::from(error)],
+        });
+    }
+}
+
+fn print_json(summary: &SummaryJson) {
+    match serde_json::to_string_pretty(summary) {
+        Ok(json) => println!("{}", json),
+        Err(err) => log::error!("Cannot serialize the summary: {}", err),
+    }
+}
+
+#[derive(Serialize)]
+struct SummaryJson {
+    schema_version: u32,
+    domain: String,
+    record: Option<String>,
+
+    /// True if no diagnostic is an error
+    valid: bool,
+    lookup_count: usize,
+    terms: Vec<TermJson>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl From<&SpfSummary> for SummaryJson {
+    fn from(summary: &SpfSummary) -> Self {
+        let diagnostics = summary
+            .diagnostics
+            .iter()
+            .map(Diagnostic::from)
+            .collect::<Vec<_>>();
+
+        SummaryJson {
+            schema_version: SCHEMA_VERSION,
+            domain: summary.domain_name.to_owned(),
+            record: Some(summary.raw_rdata.to_owned()),
+            valid: !diagnostics.iter().any(Diagnostic::is_error),
+            lookup_count: summary.lookup_count,
+            terms: terms_json(&summary.terms),
+            diagnostics,
+        }
+    }
+}
+
+/// A term of a record, fields that do not apply to the kind of term are omitted
+#[derive(Serialize, Default)]
+struct TermJson {
+    /// "directive", "modifier", "unknown" or "loop"
+    kind: &'static str,
+
+    /// The term as written in the record
+    value: String,
+    span: SpanJson,
+
+    /// The name of the mechanism or modifier (e.g. "include")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'static str>,
+
+    /// "pass", "fail", "softfail" or "neutral"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    qualifier: Option<&'static str>,
+
+    /// The domain name the term points to, none if it depends on the sender
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain_name: Option<String>,
+
+    /// Matched networks in CIDR notation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    addresses: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hosts: Option<Vec<HostJson>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validated_names: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation: Option<String>,
+
+    /// The record of an include or redirect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    record: Option<String>,
+
+    /// The terms of the record of an include or redirect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    terms: Option<Vec<TermJson>>,
+
+    /// The domain names from the checked domain up to the repeated one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chain: Option<Vec<String>>,
+
+    /// Why the term could not be resolved or is invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Serialize, Default)]
+struct SpanJson {
+    start: usize,
+    end: usize,
+}
+
+impl From<Range<usize>> for SpanJson {
+    fn from(span: Range<usize>) -> Self {
+        SpanJson {
+            start: span.start,
+            end: span.end,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HostJson {
+    name: String,
+    addresses: Vec<String>,
+}
+
+fn terms_json(terms: &[Term]) -> Vec<TermJson> {
+    terms.iter().map(term_json).collect()
+}
+
+fn term_json(term: &Term) -> TermJson {
+    match term {
+        Term::Directive(d) => {
+            let term = TermJson {
+                kind: "directive",
+                value: d.to_string(),
+                span: d.span.clone().into(),
+                qualifier: Some(match d.qualifier {
+                    None | Some(QualifierType::Pass) => "pass",
+                    Some(QualifierType::Fail) => "fail",
+                    Some(QualifierType::SoftFail) => "softfail",
+                    Some(QualifierType::Neutral) => "neutral",
+                }),
+                ..TermJson::default()
+            };
+            match &d.mechanism {
+                Mechanism::All(_) => TermJson {
+                    name: Some("all"),
+                    ..term
+                },
+                Mechanism::A(a) => TermJson {
+                    name: Some("a"),
+                    domain_name: a.domain_name.to_owned(),
+                    addresses: Some(cidrs(&a.ip_addresses, a.ip4_subnet_mask, a.ip6_subnet_mask)),
+                    ..term
+                },
+                Mechanism::Mx(mx) => TermJson {
+                    name: Some("mx"),
+                    domain_name: mx.domain_name.to_owned(),
+                    hosts: Some(
+                        mx.hosts
+                            .iter()
+                            .map(|host| HostJson {
+                                name: host.name.to_owned(),
+                                addresses: cidrs(
+                                    &host.ip_addresses,
+                                    mx.ip4_subnet_mask,
+                                    mx.ip6_subnet_mask,
+                                ),
+                            })
+                            .collect(),
+                    ),
+                    ..term
+                },
+                Mechanism::Include(i) => TermJson {
+                    name: Some("include"),
+                    domain_name: Some(i.domain_spec.to_owned()),
+                    record: Some(i.raw_rdata.to_owned()).filter(|record| !record.is_empty()),
+                    terms: Some(terms_json(&i.terms)),
+                    ..term
+                },
+                Mechanism::Ptr(p) => TermJson {
+                    name: Some("ptr"),
+                    domain_name: p.domain_name.to_owned(),
+                    validated_names: p.validated_names.to_owned(),
+                    ..term
+                },
+                Mechanism::Ip4(i) => TermJson {
+                    name: Some("ip4"),
+                    addresses: Some(cidrs(&[i.ip_address.into()], i.subnet_mask, None)),
+                    ..term
+                },
+                Mechanism::Ip6(i) => TermJson {
+                    name: Some("ip6"),
+                    addresses: Some(cidrs(&[i.ip_address.into()], None, i.subnet_mask)),
+                    ..term
+                },
+                Mechanism::Exists(e) => TermJson {
+                    name: Some("exists"),
+                    domain_name: e.domain_name.to_owned(),
+                    addresses: Some(cidrs(&e.ip_addresses, None, None)),
+                    ..term
+                },
+            }
+        }
+        Term::Modifier(m) => {
+            let term = TermJson {
+                kind: "modifier",
+                value: m.to_string(),
+                span: m.span().into(),
+                ..TermJson::default()
+            };
+            match m {
+                Modifier::Redirect(r) => TermJson {
+                    name: Some("redirect"),
+                    domain_name: Some(r.domain_spec.to_owned()),
+                    record: Some(r.raw_rdata.to_owned()).filter(|record| !record.is_empty()),
+                    terms: Some(terms_json(&r.terms)),
+                    ..term
+                },
+                Modifier::Exp(e) => TermJson {
+                    name: Some("exp"),
+                    domain_name: e.domain_name.to_owned(),
+                    explanation: e.explain_string.to_owned(),
+                    ..term
+                },
+            }
+        }
+        Term::Unknown(u) => TermJson {
+            kind: "unknown",
+            value: u.raw_rdata.to_owned(),
+            span: u.span.clone().into(),
+            reason: u
+                .error
+                .as_ref()
+                .map(|err| err.message.to_owned())
+                .or_else(|| u.reason.to_owned()),
+            ..TermJson::default()
+        },
+        Term::Loop(l) => TermJson {
+            kind: "loop",
+            value: l.raw_rdata.to_owned(),
+            span: l.span.clone().into(),
+            chain: Some(l.chain.to_owned()),
+            ..TermJson::default()
+        },
+    }
+}
+
+/// Format IP addresses in CIDR notation with the length of their address family
+fn cidrs(
+    ip_addresses: &[IpAddr],
+    ip4_subnet_mask: Option<u8>,
+    ip6_subnet_mask: Option<u8>,
+) -> Vec<String> {
+    ip_addresses
+        .iter()
+        .map(|ip| {
+            let mask = match ip {
+                IpAddr::V4(_) => ip4_subnet_mask.unwrap_or(32),
+                IpAddr::V6(_) => ip6_subnet_mask.unwrap_or(128),
+            };
+            format!("{}/{}", ip, mask)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::spf::domain::{LabelSpan, Severity, SpfRecord, SyntaxError};
+
+    #[test]
+    fn it_should_serialize_summary() {
+        // Arrange
+        let record = SpfRecord::parse("v=spf1 ip4:192.0.2.0/24 ~all");
+        let summary = SpfSummary {
+            domain_name: "example.com".to_owned(),
+            version: record.version,
+            terms: record.terms,
+            raw_rdata: record.raw_rdata,
+            lookup_count: 0,
+            diagnostics: vec![SpfError::SyntaxError(
+                SyntaxError::new("Mechanisms after 'all' found")
+                    .with_code("spf::all-not-rightmost")
+                    .with_severity(Severity::Warning)
+                    .with_src("v=spf1 ~all a")
+                    .with_src_labels(vec![LabelSpan::at(12..13, "This will be ignored")]),
+            )],
+        };
+
+        // Act
+        let json = serde_json::to_value(SummaryJson::from(&summary)).unwrap();
+
+        // Assert
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "schema_version": 1,
+                "domain": "example.com",
+                "record": "v=spf1 ip4:192.0.2.0/24 ~all",
+                "valid": true,
+                "lookup_count": 0,
+                "terms": [
+                    {
+                        "kind": "directive",
+                        "value": "ip4:192.0.2.0/24",
+                        "span": { "start": 7, "end": 23 },
+                        "name": "ip4",
+                        "qualifier": "pass",
+                        "addresses": ["192.0.2.0/24"]
+                    },
+                    {
+                        "kind": "directive",
+                        "value": "~all",
+                        "span": { "start": 24, "end": 28 },
+                        "name": "all",
+                        "qualifier": "softfail"
+                    }
+                ],
+                "diagnostics": [
+                    {
+                        "code": "spf::all-not-rightmost",
+                        "severity": "warning",
+                        "message": "Mechanisms after 'all' found",
+                        "help": null,
+                        "source": "v=spf1 ~all a",
+                        "labels": [{ "offset": 12, "length": 1, "label": "This will be ignored" }]
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_be_invalid_with_error_diagnostic() {
+        // Arrange
+        let record = SpfRecord::parse("v=spf1 -all");
+        let summary = SpfSummary {
+            domain_name: "example.com".to_owned(),
+            version: record.version,
+            terms: record.terms,
+            raw_rdata: record.raw_rdata,
+            lookup_count: 11,
+            diagnostics: vec![SpfError::SyntaxError(
+                SyntaxError::new("Max lookup count of 10 exceeded").with_code("spf::max-lookups"),
+            )],
+        };
+
+        // Act
+        let json = SummaryJson::from(&summary);
+
+        // Assert
+        assert!(!json.valid);
+        assert_eq!(json.diagnostics[0].code, "spf::max-lookups");
+        assert_eq!(json.diagnostics[0].severity, "error");
+    }
+}
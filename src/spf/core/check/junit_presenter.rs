@@ -0,0 +1,142 @@
+use crate::common::presenter::Presenter;
+use crate::spf::core::check::diagnostic::Diagnostic;
+use crate::spf::core::check::use_case::SpfSummary;
+use crate::spf::domain::SpfError;
+
+/// Prints the diagnostics as a JUnit XML report for CI pipelines,
+/// with the domain name as the test suite and a failed test case per diagnostic
+pub struct SummarySpfJunitPresenter {
+    domain_name: String,
+}
+
+impl SummarySpfJunitPresenter {
+    pub fn new(domain_name: impl Into<String>) -> Self {
+        SummarySpfJunitPresenter {
+            domain_name: domain_name.into(),
+        }
+    }
+}
+
+impl Presenter<SpfSummary, SpfError> for SummarySpfJunitPresenter {
+    fn success(&mut self, data: &SpfSummary) {
+        let diagnostics = data
+            .diagnostics
+            .iter()
+            .map(Diagnostic::from)
+            .collect::<Vec<_>>();
+        print!("{}", junit_report(&data.domain_name, &diagnostics));
+    }
+
+    fn error(&mut self, error: &SpfError) {
+        print!(
+            "{}",
+            junit_report(&self.domain_name, &[Diagnostic::from(error)])
+        );
+    }
+}
+
+/// A test suite with a failed test case per diagnostic, or a single passed one without diagnostics
+fn junit_report(domain_name: &str, diagnostics: &[Diagnostic]) -> String {
+    let domain_name = escape(domain_name);
+    let test_cases = if diagnostics.is_empty() {
+        format!(
+            "    <testcase name=\"spf\" classname=\"{}\"/>\n",
+            domain_name
+        )
+    } else {
+        diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let mut details = vec![];
+                if let Some(source) = &diagnostic.source {
+                    details.push(source.to_owned());
+                }
+                details.extend(diagnostic.labels.iter().map(|label| {
+                    format!(
+                        "at {}..{}: {}",
+                        label.offset,
+                        label.offset + label.length,
+                        label.label.to_owned().unwrap_or_default()
+                    )
+                }));
+                if let Some(help) = &diagnostic.help {
+                    details.push(format!("help: {}", help));
+                }
+
+                format!(
+                    "    <testcase name=\"{}\" classname=\"{}\">\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape(&diagnostic.code),
+                    domain_name,
+                    diagnostic.severity,
+                    escape(&diagnostic.message),
+                    escape(&details.join("\n"))
+                )
+            })
+            .collect()
+    };
+    let tests = diagnostics.len().max(1);
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"det\" tests=\"{tests}\" failures=\"{failures}\">\n  \
+         <testsuite name=\"{domain_name}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\">\n\
+         {test_cases}  \
+         </testsuite>\n\
+         </testsuites>\n",
+        failures = diagnostics.len(),
+    )
+}
+
+/// Escape the characters with a special meaning in XML text and attributes
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::spf::domain::{LabelSpan, SyntaxError};
+
+    #[test]
+    fn it_should_report_diagnostic_as_failure() {
+        // Arrange
+        let diagnostics = [Diagnostic::from(&SpfError::SyntaxError(
+            SyntaxError::new("Max lookup count of 10 exceeded")
+                .with_code("spf::max-lookups")
+                .with_src("v=spf1 a -all")
+                .with_src_labels(vec![LabelSpan::at(7..8, "11 lookups")])
+                .with_help("Remove lookups & includes."),
+        ))];
+
+        // Act
+        let report = junit_report("example.com", &diagnostics);
+
+        // Assert
+        assert_eq!(
+            report,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites name=\"det\" tests=\"1\" failures=\"1\">\n  \
+             <testsuite name=\"example.com\" tests=\"1\" failures=\"1\" errors=\"0\" skipped=\"0\">\n    \
+             <testcase name=\"spf::max-lookups\" classname=\"example.com\">\n      \
+             <failure type=\"error\" message=\"Max lookup count of 10 exceeded\">v=spf1 a -all\n\
+             at 7..8: 11 lookups\n\
+             help: Remove lookups &amp; includes.</failure>\n    \
+             </testcase>\n  \
+             </testsuite>\n\
+             </testsuites>\n"
+        );
+    }
+
+    #[test]
+    fn it_should_report_passed_test_case_for_valid_record() {
+        let report = junit_report("example.com", &[]);
+
+        assert!(report.contains("<testsuite name=\"example.com\" tests=\"1\" failures=\"0\""));
+        assert!(report.contains("<testcase name=\"spf\" classname=\"example.com\"/>"));
+    }
+}
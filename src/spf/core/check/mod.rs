@@ -1,7 +1,15 @@
 mod checks;
+mod diagnostic;
+mod json_presenter;
+mod junit_presenter;
 mod presenter;
+mod sarif_presenter;
 mod use_case;
 
 // TODO: we should export a Factorys instead of a concrete implementations
+pub use self::json_presenter::SummarySpfJsonPresenter;
+pub use self::junit_presenter::SummarySpfJunitPresenter;
+pub(in crate::spf::core) use self::presenter::print_spf_error;
 pub use self::presenter::{SummarySpfTerminalPresenter, SummarySpfWithDetailTerminalPresenter};
+pub use self::sarif_presenter::SummarySpfSarifPresenter;
 pub use self::use_case::{SpfSummary, SummarySpfQuery, SummarySpfUseCase, SummarySpfUseCaseImpl};
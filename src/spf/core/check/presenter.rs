@@ -1,6 +1,8 @@
+use std::net::IpAddr;
+
 use crate::common::presenter::Presenter;
 use crate::spf::core::check::use_case::SpfSummary;
-use crate::spf::domain::{Mechanism, Modifier, SpfError, SyntaxError, Term};
+use crate::spf::domain::{Mechanism, Modifier, SpfError, Term};
 
 #[derive(Default)]
 pub struct SummarySpfTerminalPresenter {}
@@ -13,6 +15,11 @@ impl SummarySpfTerminalPresenter {
 
 impl Presenter<SpfSummary, SpfError> for SummarySpfTerminalPresenter {
     fn success(&mut self, data: &SpfSummary) {
+        if !data.diagnostics.is_empty() {
+            data.diagnostics.iter().for_each(print_spf_error);
+            return;
+        }
+
         println!("Raw Record: '{}'", data.raw_rdata);
         println!("SPF looks good");
     }
@@ -32,6 +39,11 @@ impl SummarySpfWithDetailTerminalPresenter {
 
 impl Presenter<SpfSummary, SpfError> for SummarySpfWithDetailTerminalPresenter {
     fn success(&mut self, data: &SpfSummary) {
+        if !data.diagnostics.is_empty() {
+            data.diagnostics.iter().for_each(print_spf_error);
+            return;
+        }
+
         println!("Raw Record: '{}'", data.raw_rdata);
 
         // TODO: this should be enabled with a "detail" flag
@@ -53,39 +65,66 @@ impl SummarySpfWithDetailTerminalPresenter {
                 if let Mechanism::Include(i) = &t.mechanism {
                     let tabs = format!("{}\t", indent);
 
-                    println!("{} Raw Record: {}", tabs, i.raw_rdata);
-                    Self::recursive_print(&tabs, &i.terms);
+                    if i.raw_rdata.is_empty() {
+                        println!("{} Not resolved: domain-spec depends on the sender", tabs);
+                    } else {
+                        println!("{} Raw Record: {}", tabs, i.raw_rdata);
+                        Self::recursive_print(&tabs, &i.terms);
+                    }
                 } else if let Mechanism::A(i) = &t.mechanism {
                     let tabs = format!("{}\t", indent);
 
-                    let ip_addresses = if let Some(mask) = i.subnet_mask {
-                        i.ip_addresses
-                            .iter()
-                            .map(|ip| format!("{}/{}", ip, mask))
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    } else {
-                        i.ip_addresses
-                            .iter()
-                            .map(|ip| ip.to_string())
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    };
+                    let ip_addresses =
+                        format_ip_addresses(&i.ip_addresses, i.ip4_subnet_mask, i.ip6_subnet_mask);
 
                     println!("{} IP: {}", tabs, ip_addresses);
                 } else if let Mechanism::Mx(i) = &t.mechanism {
                     let tabs = format!("{}\t", indent);
-                    let hosts = if let Some(mask) = i.subnet_mask {
-                        i.hosts
-                            .iter()
-                            .map(|h| format!("{}/{}", h, mask))
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    } else {
-                        i.hosts.join(", ")
-                    };
+                    let hosts = i
+                        .hosts
+                        .iter()
+                        .map(|h| {
+                            let ip_addresses = format_ip_addresses(
+                                &h.ip_addresses,
+                                i.ip4_subnet_mask,
+                                i.ip6_subnet_mask,
+                            );
+                            format!("{} ({})", h.name, ip_addresses)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
 
                     println!("{} MX: {}", tabs, &hosts);
+                } else if let Mechanism::Exists(e) = &t.mechanism {
+                    let tabs = format!("{}\t", indent);
+
+                    match &e.domain_name {
+                        None => {
+                            println!("{} Not resolved: domain-spec depends on the sender", tabs)
+                        }
+                        Some(domain_name) if e.ip_addresses.is_empty() => {
+                            println!("{} Domain: {} (does not exist)", tabs, domain_name)
+                        }
+                        Some(domain_name) => println!("{} Domain: {} (exists)", tabs, domain_name),
+                    }
+                } else if let Mechanism::Ptr(p) = &t.mechanism {
+                    let tabs = format!("{}\t", indent);
+
+                    match (&p.domain_name, &p.validated_names) {
+                        (Some(domain_name), Some(names)) if !names.is_empty() => println!(
+                            "{} Domain: {} (validated: {})",
+                            tabs,
+                            domain_name,
+                            names.join(", ")
+                        ),
+                        (Some(domain_name), Some(_)) => {
+                            println!("{} Domain: {} (no validated host names)", tabs, domain_name)
+                        }
+                        (Some(domain_name), None) => println!("{} Domain: {}", tabs, domain_name),
+                        (None, _) => {
+                            println!("{} Not resolved: domain-spec depends on the sender", tabs)
+                        }
+                    }
                 } else if let Mechanism::Ip4(i) = &t.mechanism {
                     let tabs = format!("{}\t", indent);
                     let ip_address = if let Some(mask) = i.subnet_mask {
@@ -112,24 +151,90 @@ impl SummarySpfWithDetailTerminalPresenter {
                 if let Modifier::Redirect(r) = &m {
                     let tabs = format!("{}\t", indent);
 
-                    println!("{}Raw Record: {}", tabs, r.raw_rdata);
-                    Self::recursive_print(&tabs, &r.terms);
-                } else {
-                    println!("{}- Modifier '{}' not implemented yet", indent, m);
+                    if r.raw_rdata.is_empty() {
+                        println!("{}Not resolved: domain-spec depends on the sender", tabs);
+                    } else {
+                        println!("{}Raw Record: {}", tabs, r.raw_rdata);
+                        Self::recursive_print(&tabs, &r.terms);
+                    }
+                } else if let Modifier::Exp(e) = &m {
+                    let tabs = format!("{}\t", indent);
+
+                    match (&e.domain_name, &e.explain_string) {
+                        (None, _) => {
+                            println!("{}Not resolved: domain-spec depends on the sender", tabs)
+                        }
+                        (Some(domain_name), None) => {
+                            println!("{}No explanation found at {}", tabs, domain_name)
+                        }
+                        (Some(_), Some(explain_string)) => {
+                            println!("{}Explanation: {}", tabs, explain_string)
+                        }
+                    }
                 }
             }
-            Term::Unknown(u) => {
-                println!("{}- Unknown term: {}", indent, u.raw_rdata);
+            Term::Unknown(u) => match &u.error {
+                Some(err) => {
+                    println!("{}- Invalid term: {}", indent, u.raw_rdata);
+                    println!("{}\t{}", indent, err.message);
+                }
+                None => {
+                    println!("{}- Unknown term: {}", indent, u.raw_rdata);
+                    if let Some(reason) = &u.reason {
+                        println!("{}\t {}", indent, reason);
+                    }
+                }
+            },
+            Term::Loop(l) => {
+                println!("{}- {}", indent, l.raw_rdata);
+                println!("{}\t Include loop: {}", indent, l.chain.join(" -> "));
             }
         });
     }
 }
 
-fn print_spf_error(error: &SpfError) {
+/// Format IP addresses with the CIDR length of their address family
+fn format_ip_addresses(
+    ip_addresses: &[IpAddr],
+    ip4_subnet_mask: Option<u8>,
+    ip6_subnet_mask: Option<u8>,
+) -> String {
+    ip_addresses
+        .iter()
+        .map(|ip| {
+            let mask = match ip {
+                IpAddr::V4(_) => ip4_subnet_mask,
+                IpAddr::V6(_) => ip6_subnet_mask,
+            };
+            match mask {
+                Some(mask) => format!("{}/{}", ip, mask),
+                None => ip.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(in crate::spf::core) fn print_spf_error(error: &SpfError) {
     match error {
         SpfError::NoSpfRecordFound(message) => {
             eprintln!("Error: {}", message);
         }
+        SpfError::MultipleSpfRecordsFound { domain_name, .. } => {
+            eprintln!("Error: Multiple SPF records found for '{}'", domain_name);
+        }
+        SpfError::IncludeLoop { chain } => {
+            eprintln!("Error: Include loop: {}", chain.join(" -> "));
+        }
+        SpfError::IncludeDepthExceeded { chain } => {
+            eprintln!("Error: Max include depth exceeded: {}", chain.join(" -> "));
+        }
+        SpfError::TempError(err) => {
+            eprintln!("Temporary DNS error: {}", err);
+        }
+        SpfError::PermError(err) => {
+            eprintln!("DNS error: {}", err);
+        }
         SpfError::CheckFailed(err) => {
             eprintln!("Check failed: {}", err.summary);
             eprintln!("\t {}", err.description);
@@ -140,23 +245,3 @@ fn print_spf_error(error: &SpfError) {
         }
     }
 }
-
-impl From<SyntaxError> for miette::Report {
-    fn from(err: SyntaxError) -> Self {
-        let mut diag = miette::MietteDiagnostic::new(err.message.to_string());
-        if let Some(help) = err.help {
-            diag = diag.with_help(help);
-        }
-        if let Some(src_labels) = err.src_labels {
-            diag = diag.with_labels(src_labels);
-        }
-        if let Some(severity) = err.severity {
-            diag = diag.with_severity(severity);
-        }
-        let mut report = miette::Report::from(diag);
-        if let Some(src) = err.src {
-            report = report.with_source_code(src)
-        }
-        report
-    }
-}
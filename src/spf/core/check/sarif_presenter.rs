@@ -0,0 +1,228 @@
+use serde_json::{json, Value};
+
+use crate::common::presenter::Presenter;
+use crate::spf::core::check::diagnostic::Diagnostic;
+use crate::spf::core::check::use_case::SpfSummary;
+use crate::spf::domain::SpfError;
+
+/// Prints the diagnostics as a SARIF 2.1.0 log for code scanning in CI pipelines
+pub struct SummarySpfSarifPresenter {
+    domain_name: String,
+}
+
+impl SummarySpfSarifPresenter {
+    pub fn new(domain_name: impl Into<String>) -> Self {
+        SummarySpfSarifPresenter {
+            domain_name: domain_name.into(),
+        }
+    }
+}
+
+impl Presenter<SpfSummary, SpfError> for SummarySpfSarifPresenter {
+    fn success(&mut self, data: &SpfSummary) {
+        let diagnostics = data
+            .diagnostics
+            .iter()
+            .map(Diagnostic::from)
+            .collect::<Vec<_>>();
+        println!("{:#}", sarif_log(&data.domain_name, &diagnostics));
+    }
+
+    fn error(&mut self, error: &SpfError) {
+        println!(
+            "{:#}",
+            sarif_log(&self.domain_name, &[Diagnostic::from(error)])
+        );
+    }
+}
+
+/// A single run with one result per diagnostic, the domain name is the artifact
+/// and regions are columns of the single line source of the diagnostic
+fn sarif_log(domain_name: &str, diagnostics: &[Diagnostic]) -> Value {
+    let mut rules: Vec<Value> = vec![];
+    for diagnostic in diagnostics {
+        if rules.iter().all(|rule| rule["id"] != diagnostic.code) {
+            rules.push(json!({
+                "id": diagnostic.code,
+                "shortDescription": { "text": diagnostic.message },
+                "help": { "text": diagnostic.help.to_owned().unwrap_or_default() },
+            }));
+        }
+    }
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let location = |region: Value| {
+                json!({
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": domain_name },
+                        "region": region,
+                    }
+                })
+            };
+            // A region needs a start line, columns are 1-based and the end column is exclusive
+            let region = |source: &str, offset: usize, length: usize| {
+                json!({
+                    "startLine": 1,
+                    "startColumn": offset + 1,
+                    "endColumn": offset + length + 1,
+                    "charOffset": offset,
+                    "charLength": length,
+                    "snippet": { "text": source },
+                })
+            };
+            let locations = match &diagnostic.source {
+                // Without a source there is nothing to point at
+                None => vec![json!({
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": domain_name },
+                    }
+                })],
+                Some(source) if diagnostic.labels.is_empty() => {
+                    vec![location(region(source, 0, source.chars().count()))]
+                }
+                Some(source) => diagnostic
+                    .labels
+                    .iter()
+                    .map(|label| {
+                        let mut location = location(region(source, label.offset, label.length));
+                        if let Some(label) = &label.label {
+                            location["message"] = json!({ "text": label });
+                        }
+                        location
+                    })
+                    .collect(),
+            };
+            let message = match &diagnostic.help {
+                Some(help) => format!("{} ({})", diagnostic.message, help),
+                None => diagnostic.message.to_owned(),
+            };
+
+            json!({
+                "ruleId": diagnostic.code,
+                "level": match diagnostic.severity {
+                    "warning" => "warning",
+                    "advice" => "note",
+                    _ => "error",
+                },
+                "message": { "text": message },
+                "locations": locations,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "det",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/CSalih/dns-email-toolkit",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::spf::domain::{LabelSpan, Severity, SyntaxError};
+
+    #[test]
+    fn it_should_report_diagnostic_as_result() {
+        // Arrange
+        let diagnostics = [Diagnostic::from(&SpfError::SyntaxError(
+            SyntaxError::new("Use of the 'ptr' mechanism is not recommended")
+                .with_code("spf::ptr-mechanism")
+                .with_severity(Severity::Warning)
+                .with_src("v=spf1 ptr -all")
+                .with_src_labels(vec![LabelSpan::at(7..10, "'ptr' is slow")])
+                .with_help("Replace 'ptr'."),
+        ))];
+
+        // Act
+        let log = sarif_log("example.com", &diagnostics);
+
+        // Assert
+        let run = &log["runs"][0];
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["id"],
+            "spf::ptr-mechanism"
+        );
+        assert_eq!(
+            run["results"][0],
+            json!({
+                "ruleId": "spf::ptr-mechanism",
+                "level": "warning",
+                "message": { "text": "Use of the 'ptr' mechanism is not recommended (Replace 'ptr'.)" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "example.com" },
+                        "region": {
+                            "startLine": 1,
+                            "startColumn": 8,
+                            "endColumn": 11,
+                            "charOffset": 7,
+                            "charLength": 3,
+                            "snippet": { "text": "v=spf1 ptr -all" }
+                        }
+                    },
+                    "message": { "text": "'ptr' is slow" }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_report_no_results_for_valid_record() {
+        let log = sarif_log("example.com", &[]);
+
+        assert_eq!(log["runs"][0]["results"], json!([]));
+    }
+
+    #[test]
+    fn it_should_leave_out_the_region_without_source() {
+        // Arrange
+        let diagnostics = [Diagnostic::from(&SpfError::NoSpfRecordFound(
+            "No SPF record found for 'example.com'".to_owned(),
+        ))];
+
+        // Act
+        let log = sarif_log("example.com", &diagnostics);
+
+        // Assert
+        assert_eq!(
+            log["runs"][0]["results"][0]["locations"],
+            json!([{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "example.com" }
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn it_should_point_at_the_whole_source_without_labels() {
+        // Arrange
+        let diagnostics = [Diagnostic::from(&SpfError::SyntaxError(
+            SyntaxError::new("Record is too long").with_src("v=spf1 -all"),
+        ))];
+
+        // Act
+        let log = sarif_log("example.com", &diagnostics);
+
+        // Assert
+        let region = &log["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert_eq!(region["startColumn"], 1);
+        assert_eq!(region["endColumn"], 12);
+    }
+}
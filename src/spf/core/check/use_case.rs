@@ -1,25 +1,39 @@
+use async_trait::async_trait;
+
+use crate::common::outcome::Outcome;
 use crate::common::presenter::Presenter;
 use crate::dns::core::dns_resolver::DnsResolver;
 use crate::spf::core::check::checks::{
-    check_is_ascii, check_lookup_count, check_max_txt_length, check_no_redirect_with_all,
-    check_redirect_is_rightmost, check_version,
+    check_exp_target_exists, check_include_loop, check_is_ascii, check_lookup_count,
+    check_lookup_errors, check_max_txt_length, check_mx_host_count, check_no_redirect_with_all,
+    check_ptr_mechanism, check_redirect_is_rightmost, check_single_exp_modifier,
+    check_single_spf_record, check_syntax, check_version, check_void_lookup_count, count_lookup,
+    failed_lookups,
 };
 use crate::spf::core::resolver::use_case::{ResolveSpfQuery, ResolveSpfUseCase};
 use crate::spf::core::ResolveSpfUseCaseImpl;
-use crate::spf::domain::{SpfError, Term, Version};
+use crate::spf::domain::{Severity, SpfError, SpfRecord, Term, Version};
 
 use super::checks::{check_all_is_rightmost, check_has_unknown_term};
 
+#[async_trait(?Send)]
 pub trait SummarySpfUseCase {
     /// Summary the SPF record of a domain name.
-    fn execute(
+    ///
+    /// The presenter gets the summary with its diagnostics if a record was resolved,
+    /// otherwise the error that prevented resolving it.
+    /// Returns the outcome of the checks.
+    async fn execute(
         &mut self,
         query: &SummarySpfQuery,
         presenter: Box<dyn Presenter<SpfSummary, SpfError>>,
-    );
+    ) -> Outcome;
 }
 
 pub struct SpfSummary {
+    /// The checked domain name
+    pub domain_name: String,
+
     /// The version of the SPF record (e.g. "spf1")
     pub version: Version,
 
@@ -28,11 +42,20 @@ pub struct SpfSummary {
 
     /// RDATA of a single DNS TXT resource record
     pub raw_rdata: String,
+
+    /// The number of terms that cause DNS lookups, including nested records
+    pub lookup_count: usize,
+
+    /// The errors and warnings of the checks, the record is valid without errors
+    pub diagnostics: Vec<SpfError>,
 }
 
 pub struct SummarySpfQuery {
     pub domain_name: String,
     pub record: Option<String>,
+
+    /// Only check the syntax of the given record without any DNS lookups
+    pub offline: bool,
 }
 
 pub struct SummarySpfUseCaseImpl<'a> {
@@ -40,29 +63,53 @@ pub struct SummarySpfUseCaseImpl<'a> {
 }
 
 impl<'a> SummarySpfUseCaseImpl<'a> {
-    pub fn new(dns_resolver: &'a mut dyn DnsResolver) -> Self {
+    pub fn new(dns_resolver: &'a dyn DnsResolver) -> Self {
         SummarySpfUseCaseImpl {
             spf_resolver: Box::new(ResolveSpfUseCaseImpl::new(dns_resolver)),
         }
     }
 }
 
+#[async_trait(?Send)]
 impl<'a> SummarySpfUseCase for SummarySpfUseCaseImpl<'a> {
-    fn execute(
+    async fn execute(
         &mut self,
         query: &SummarySpfQuery,
         mut presenter: Box<dyn Presenter<SpfSummary, SpfError>>,
-    ) {
-        let spf_summary = self.spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: query.domain_name.to_owned(),
-            record: query.record.to_owned(),
-        });
+    ) -> Outcome {
+        let spf_summary = match (&query.record, query.offline) {
+            (Some(record), true) => Ok(SpfRecord::parse(record)),
+            (None, true) => Err(Box::new(SpfError::NoSpfRecordFound(
+                "A record is required to check it offline".to_owned(),
+            ))),
+            (_, false) => {
+                self.spf_resolver
+                    .resolve(&ResolveSpfQuery {
+                        domain_name: query.domain_name.to_owned(),
+                        record: query.record.to_owned(),
+                        macro_context: None,
+                    })
+                    .await
+            }
+        };
 
-        let Ok(spf_summary) = spf_summary else {
-            if let Some(err) = spf_summary.err() {
-                presenter.error(&err);
+        let spf_summary = match spf_summary {
+            Ok(spf_summary) => spf_summary,
+            Err(err) => {
+                match *err {
+                    SpfError::MultipleSpfRecordsFound { records, .. } => {
+                        if let Err(err) = check_single_spf_record(&records) {
+                            presenter.error(&(*err).into());
+                        }
+                    }
+                    SpfError::TempError(err) => {
+                        presenter.error(&SpfError::TempError(err));
+                        return Outcome::DnsFailure;
+                    }
+                    err => presenter.error(&err),
+                }
+                return Outcome::Errors;
             }
-            return;
         };
 
         // checks
@@ -76,12 +123,36 @@ impl<'a> SummarySpfUseCase for SummarySpfUseCaseImpl<'a> {
         if let Err(err) = check_version(&spf_summary.raw_rdata) {
             check_errors.push((*err).into());
         }
+        if let Err(err) = check_syntax(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
         if let Err(err) = check_lookup_count(&spf_summary.terms, &spf_summary.raw_rdata) {
             check_errors.push((*err).into());
         }
+        if let Err(err) = check_lookup_errors(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_include_loop(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_mx_host_count(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_void_lookup_count(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
         if let Err(err) = check_has_unknown_term(&spf_summary.terms, &spf_summary.raw_rdata) {
             check_errors.push((*err).into());
         }
+        if let Err(err) = check_ptr_mechanism(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_single_exp_modifier(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
+        if let Err(err) = check_exp_target_exists(&spf_summary.terms, &spf_summary.raw_rdata) {
+            check_errors.push((*err).into());
+        }
         if let Err(err) = check_all_is_rightmost(&spf_summary.terms, &spf_summary.raw_rdata) {
             check_errors.push((*err).into());
         }
@@ -92,16 +163,229 @@ impl<'a> SummarySpfUseCase for SummarySpfUseCaseImpl<'a> {
             check_errors.push((*err).into());
         }
 
-        if check_errors.is_empty() {
-            presenter.success(&SpfSummary {
-                version: spf_summary.version,
-                terms: spf_summary.terms,
-                raw_rdata: spf_summary.raw_rdata,
-            });
-        } else {
-            check_errors.iter().for_each(|err| {
-                presenter.error(err);
-            });
+        let outcome = outcome(&spf_summary.terms, &check_errors);
+        presenter.success(&SpfSummary {
+            domain_name: query.domain_name.to_owned(),
+            lookup_count: count_lookup(&spf_summary.terms),
+            version: spf_summary.version,
+            terms: spf_summary.terms,
+            raw_rdata: spf_summary.raw_rdata,
+            diagnostics: check_errors,
+        });
+
+        outcome
+    }
+}
+
+/// The worst outcome of the diagnostics, a term with a temporarily failed lookup
+/// makes the checks incomplete
+fn outcome(terms: &[Term], diagnostics: &[SpfError]) -> Outcome {
+    let has_temporary_lookup_error = failed_lookups(terms).iter().any(|unknown| {
+        unknown
+            .lookup_error
+            .as_ref()
+            .is_some_and(|err| err.is_temporary())
+    });
+    if has_temporary_lookup_error {
+        return Outcome::DnsFailure;
+    }
+
+    diagnostics
+        .iter()
+        .map(|diagnostic| match diagnostic.severity() {
+            Severity::Error => Outcome::Errors,
+            Severity::Warning => Outcome::Warnings,
+            Severity::Advice => Outcome::Ok,
+        })
+        .max()
+        .unwrap_or(Outcome::Ok)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::dns::core::dns_resolver::MockDnsResolver;
+    use crate::dns::domain::{DnsError, TxtRecord};
+
+    struct ErrorPresenter {
+        errors: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Presenter<SpfSummary, SpfError> for ErrorPresenter {
+        fn success(&mut self, data: &SpfSummary) {
+            data.diagnostics.iter().for_each(|err| self.error(err));
+        }
+
+        fn error(&mut self, error: &SpfError) {
+            let message = match error {
+                SpfError::NoSpfRecordFound(message) => message.to_owned(),
+                SpfError::MultipleSpfRecordsFound { domain_name, .. } => domain_name.to_owned(),
+                SpfError::IncludeLoop { chain } | SpfError::IncludeDepthExceeded { chain } => {
+                    chain.join(" -> ")
+                }
+                SpfError::TempError(err) | SpfError::PermError(err) => err.to_string(),
+                SpfError::CheckFailed(err) => err.summary.to_owned(),
+                SpfError::SyntaxError(err) => err.message.to_owned(),
+            };
+            self.errors.borrow_mut().push(message);
         }
     }
+
+    #[tokio::test]
+    async fn it_should_check_record_offline_without_dns_lookups() {
+        // Arrange
+        // The mock panics on any DNS query
+        let dns_resolver = MockDnsResolver::new();
+        let errors = Rc::new(RefCell::new(vec![]));
+        let presenter = Box::new(ErrorPresenter {
+            errors: errors.clone(),
+        });
+        let mut summary_spf_use_case = SummarySpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        summary_spf_use_case
+            .execute(
+                &SummarySpfQuery {
+                    domain_name: "example.com".to_owned(),
+                    record: Some(
+                        "v=spf1 include:_spf.example.com mx a:%{x}.example.com -all".to_owned(),
+                    ),
+                    offline: true,
+                },
+                presenter,
+            )
+            .await;
+
+        // Assert
+        assert_eq!(
+            *errors.borrow(),
+            vec!["Invalid macro string: 'x' is not a valid macro letter"]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_require_record_offline() {
+        // Arrange
+        let dns_resolver = MockDnsResolver::new();
+        let errors = Rc::new(RefCell::new(vec![]));
+        let presenter = Box::new(ErrorPresenter {
+            errors: errors.clone(),
+        });
+        let mut summary_spf_use_case = SummarySpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        summary_spf_use_case
+            .execute(
+                &SummarySpfQuery {
+                    domain_name: "example.com".to_owned(),
+                    record: None,
+                    offline: true,
+                },
+                presenter,
+            )
+            .await;
+
+        // Assert
+        assert_eq!(errors.borrow().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_warnings_outcome() {
+        // Arrange
+        let dns_resolver = MockDnsResolver::new();
+        let presenter = Box::new(ErrorPresenter {
+            errors: Rc::new(RefCell::new(vec![])),
+        });
+        let mut summary_spf_use_case = SummarySpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let outcome = summary_spf_use_case
+            .execute(
+                &SummarySpfQuery {
+                    domain_name: "example.com".to_owned(),
+                    record: Some("v=spf1 ptr -all".to_owned()),
+                    offline: true,
+                },
+                presenter,
+            )
+            .await;
+
+        // Assert
+        assert_eq!(outcome, Outcome::Warnings);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_dns_failure_outcome() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .returning(|query| Err(DnsError::Timeout(query.domain_name.to_owned())));
+        let presenter = Box::new(ErrorPresenter {
+            errors: Rc::new(RefCell::new(vec![])),
+        });
+        let mut summary_spf_use_case = SummarySpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let outcome = summary_spf_use_case
+            .execute(
+                &SummarySpfQuery {
+                    domain_name: "example.com".to_owned(),
+                    record: None,
+                    offline: false,
+                },
+                presenter,
+            )
+            .await;
+
+        // Assert
+        assert_eq!(outcome, Outcome::DnsFailure);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_dns_failure_outcome_for_nested_lookup_error() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().returning(|query| {
+            let record = match query.domain_name.as_str() {
+                "example.com" => "v=spf1 include:_spf.example.com -all",
+                _ => "v=spf1 a:mail.example.com -all",
+            };
+            Ok(TxtRecord {
+                records: vec![vec![record.to_owned()]],
+                ttl: 3600,
+            })
+        });
+        dns_resolver
+            .expect_query_a()
+            .returning(|query| Err(DnsError::ServFail(query.domain_name.to_owned())));
+        dns_resolver
+            .expect_query_aaaa()
+            .returning(|query| Err(DnsError::ServFail(query.domain_name.to_owned())));
+        let errors = Rc::new(RefCell::new(vec![]));
+        let presenter = Box::new(ErrorPresenter {
+            errors: errors.clone(),
+        });
+        let mut summary_spf_use_case = SummarySpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let outcome = summary_spf_use_case
+            .execute(
+                &SummarySpfQuery {
+                    domain_name: "example.com".to_owned(),
+                    record: None,
+                    offline: false,
+                },
+                presenter,
+            )
+            .await;
+
+        // Assert
+        assert_eq!(outcome, Outcome::DnsFailure);
+        assert!(errors.borrow().contains(&"DNS lookup failed".to_owned()));
+    }
 }
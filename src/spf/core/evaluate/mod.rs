@@ -0,0 +1,7 @@
+mod presenter;
+mod use_case;
+
+pub use self::presenter::EvaluateSpfTerminalPresenter;
+pub use self::use_case::{
+    EvaluateSpfQuery, EvaluateSpfUseCase, EvaluateSpfUseCaseImpl, SpfEvaluation,
+};
@@ -0,0 +1,42 @@
+use crate::common::presenter::Presenter;
+use crate::spf::core::check::print_spf_error;
+use crate::spf::core::evaluate::use_case::SpfEvaluation;
+use crate::spf::domain::SpfError;
+
+#[derive(Default)]
+pub struct EvaluateSpfTerminalPresenter {}
+
+impl EvaluateSpfTerminalPresenter {
+    pub fn new() -> Self {
+        EvaluateSpfTerminalPresenter::default()
+    }
+}
+
+impl Presenter<SpfEvaluation, SpfError> for EvaluateSpfTerminalPresenter {
+    fn success(&mut self, data: &SpfEvaluation) {
+        println!("Domain: {}", data.domain_name);
+        println!("IP: {}", data.ip_address);
+        println!("Sender: {}", data.sender);
+        if let Some(helo) = &data.helo {
+            println!("HELO: {}", helo);
+        }
+        println!("Result: {}", data.result);
+        println!("\t {}", data.result.description());
+        if !data.matched_terms.is_empty() {
+            println!("Matched: {}", data.matched_terms.join(" -> "));
+        }
+        if let Some(explanation) = &data.explanation {
+            println!("Explanation: {}", explanation);
+        }
+        if let Some(error) = &data.error {
+            println!("Error: {}", error);
+        }
+        println!("DNS lookups: {}", data.lookup_count);
+        if data.void_lookup_count > 0 {
+            println!("Void lookups: {}", data.void_lookup_count);
+        }
+    }
+    fn error(&mut self, error: &SpfError) {
+        print_spf_error(error);
+    }
+}
@@ -0,0 +1,1280 @@
+use std::net::IpAddr;
+
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt};
+
+use crate::common::outcome::Outcome;
+use crate::common::presenter::Presenter;
+use crate::dns::core::dns_resolver::DnsResolver;
+use crate::dns::domain::DnsError;
+use crate::spf::core::resolver::use_case::{
+    chain_name, ResolveSpfQuery, MAX_LOOKUP_COUNT, MAX_VOID_LOOKUP_COUNT,
+};
+use crate::spf::core::ResolveSpfUseCaseImpl;
+use crate::spf::domain::{
+    MacroContext, Mechanism, Modifier, QualifierType, SpfError, SpfRecord, SpfResult, Term,
+};
+
+#[async_trait(?Send)]
+pub trait EvaluateSpfUseCase {
+    /// Evaluate the SPF record of a domain name for a sender IP address
+    /// (`check_host()` in RFC 7208 section 4).
+    async fn execute(
+        &mut self,
+        query: &EvaluateSpfQuery,
+        presenter: Box<dyn Presenter<SpfEvaluation, SpfError>>,
+    ) -> Outcome;
+}
+
+pub struct EvaluateSpfQuery {
+    /// The domain name that provides the sought-after authorization information
+    pub domain_name: String,
+
+    /// The IP address of the SMTP client that is emitting the mail
+    pub ip_address: IpAddr,
+
+    /// The "MAIL FROM" identity (e.g. "user@example.com")
+    pub sender: Option<String>,
+
+    /// The domain name given in the SMTP HELO or EHLO command
+    pub helo: Option<String>,
+
+    /// The SPF record to evaluate. If not provided, the record will be fetched from DNS.
+    pub record: Option<String>,
+}
+
+pub struct SpfEvaluation {
+    /// The evaluated domain name
+    pub domain_name: String,
+
+    /// The IP address of the SMTP client
+    pub ip_address: IpAddr,
+
+    /// The "MAIL FROM" identity used for the evaluation
+    pub sender: String,
+
+    /// The domain name given in the SMTP HELO or EHLO command
+    pub helo: Option<String>,
+
+    /// The result of the evaluation
+    pub result: SpfResult,
+
+    /// The terms that determined the result, from the top-level record down to nested includes
+    pub matched_terms: Vec<String>,
+
+    /// The explanation of the domain owner for a failing result
+    pub explanation: Option<String>,
+
+    /// Why the record could not be evaluated (e.g. the failing DNS query of a temperror)
+    pub error: Option<String>,
+
+    /// The number of terms that caused DNS queries
+    pub lookup_count: usize,
+
+    /// The number of DNS queries that returned no answer
+    pub void_lookup_count: usize,
+}
+
+/// Evaluates the terms of a record in order, each term is resolved only when it is reached
+pub struct EvaluateSpfUseCaseImpl<'a> {
+    resolver: ResolveSpfUseCaseImpl<'a>,
+}
+
+impl<'a> EvaluateSpfUseCaseImpl<'a> {
+    pub fn new(dns_resolver: &'a dyn DnsResolver) -> Self {
+        EvaluateSpfUseCaseImpl {
+            resolver: ResolveSpfUseCaseImpl::new(dns_resolver),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a> EvaluateSpfUseCase for EvaluateSpfUseCaseImpl<'a> {
+    async fn execute(
+        &mut self,
+        query: &EvaluateSpfQuery,
+        mut presenter: Box<dyn Presenter<SpfEvaluation, SpfError>>,
+    ) -> Outcome {
+        let sender = sender_identity(query.sender.as_deref(), &query.domain_name);
+        let macro_context = MacroContext::new(&sender, query.ip_address, query.helo.to_owned());
+        let resolve_query = ResolveSpfQuery {
+            domain_name: query.domain_name.to_owned(),
+            record: query.record.to_owned(),
+            macro_context: Some(macro_context),
+        };
+
+        let mut lookups = Lookups::default();
+        let evaluation = match self.resolver.fetch_record(&resolve_query).await {
+            Ok(spf_record) => {
+                let ip = query.ip_address.to_canonical();
+                let chain = [chain_name(&query.domain_name)];
+                self.check_host(&ip, spf_record, &resolve_query, &chain, &mut lookups, true)
+                    .await
+            }
+            Err(err) => match *err {
+                SpfError::NoSpfRecordFound(_) => Evaluation::new(SpfResult::None),
+                SpfError::TempError(err) => Evaluation::new(SpfResult::TempError).with_error(err),
+                err => Evaluation::new(SpfResult::PermError).with_error(err),
+            },
+        };
+
+        presenter.success(&SpfEvaluation {
+            domain_name: query.domain_name.to_owned(),
+            ip_address: query.ip_address,
+            sender,
+            helo: query.helo.to_owned(),
+            result: evaluation.result,
+            matched_terms: evaluation.matched_terms,
+            explanation: evaluation.explanation,
+            error: evaluation.error,
+            lookup_count: lookups.count,
+            void_lookup_count: lookups.void_count,
+        });
+
+        outcome(evaluation.result)
+    }
+}
+
+/// A failing result is an error, a softfail a warning and a temperror makes the evaluation incomplete
+fn outcome(result: SpfResult) -> Outcome {
+    match result {
+        SpfResult::TempError => Outcome::DnsFailure,
+        SpfResult::PermError | SpfResult::Fail => Outcome::Errors,
+        SpfResult::SoftFail => Outcome::Warnings,
+        SpfResult::Pass | SpfResult::Neutral | SpfResult::None => Outcome::Ok,
+    }
+}
+
+/// The DNS lookups of an evaluation, limited by RFC 7208 section 4.6.4
+#[derive(Default)]
+struct Lookups {
+    count: usize,
+    void_count: usize,
+}
+
+impl Lookups {
+    /// Counts the lookup of a term, returns false if the limit is exceeded
+    fn add(&mut self) -> bool {
+        self.count += 1;
+        self.count <= MAX_LOOKUP_COUNT
+    }
+
+    /// Counts a lookup without answer, returns false if the limit is exceeded
+    fn add_void(&mut self) -> bool {
+        self.void_count += 1;
+        self.void_count <= MAX_VOID_LOOKUP_COUNT
+    }
+}
+
+/// Intermediate result of the evaluation of a single SPF record
+struct Evaluation {
+    result: SpfResult,
+    matched_terms: Vec<String>,
+    explanation: Option<String>,
+    error: Option<String>,
+}
+
+impl Evaluation {
+    fn new(result: SpfResult) -> Self {
+        Evaluation {
+            result,
+            matched_terms: vec![],
+            explanation: None,
+            error: None,
+        }
+    }
+
+    fn with_error(mut self, error: impl ToString) -> Self {
+        self.error = Some(error.to_string());
+        self
+    }
+
+    fn matched_by(mut self, term: impl ToString) -> Self {
+        self.matched_terms.insert(0, term.to_string());
+        self
+    }
+}
+
+impl<'a> EvaluateSpfUseCaseImpl<'a> {
+    /// Evaluate the record of the domain name of the query, which was reached by the chain of
+    /// domain names (outermost first, including the domain name itself).
+    ///
+    /// Only the explanation of the top-level or a redirected record is used. The future is boxed
+    /// as included and redirected records are evaluated recursively.
+    fn check_host<'b>(
+        &'b self,
+        ip: &'b IpAddr,
+        spf_record: SpfRecord,
+        query: &'b ResolveSpfQuery,
+        chain: &'b [String],
+        lookups: &'b mut Lookups,
+        explain: bool,
+    ) -> BoxFuture<'b, Evaluation> {
+        async move {
+            let terms = spf_record.terms;
+
+            // A syntax error anywhere in the record results in a permerror before evaluation
+            if let Some(invalid_term) = terms.iter().find_map(|term| match term {
+                Term::Unknown(u) if u.error.is_some() => Some(&u.raw_rdata),
+                _ => None,
+            }) {
+                return Evaluation::new(SpfResult::PermError).matched_by(invalid_term);
+            }
+
+            // The modifiers "redirect" and "exp" must not appear more than once (RFC 7208 section 6)
+            for modifier_name in ["redirect=", "exp="] {
+                let mut modifiers = terms
+                    .iter()
+                    .filter(|term| term_starts_with(term, modifier_name));
+                if let (Some(_), Some(duplicate)) = (modifiers.next(), modifiers.next()) {
+                    return Evaluation::new(SpfResult::PermError)
+                        .matched_by(term_to_string(duplicate));
+                }
+            }
+
+            let mut directives = vec![];
+            let mut redirect = None;
+            let mut exp = None;
+            for term in terms {
+                match term {
+                    Term::Directive(directive) => directives.push(directive),
+                    Term::Modifier(Modifier::Redirect(r)) => redirect = Some(Ok(r)),
+                    Term::Modifier(Modifier::Exp(e)) => exp = Some(e),
+                    Term::Unknown(_) | Term::Loop(_) if term_starts_with(&term, "redirect=") => {
+                        redirect = Some(Err(term_to_string(&term)))
+                    }
+                    // An include loop would never terminate (RFC 7208 section 4.6.4)
+                    Term::Loop(l) => {
+                        return Evaluation::new(SpfResult::PermError).matched_by(&l.raw_rdata)
+                    }
+                    // Unrecognized modifiers are ignored (RFC 7208 section 6)
+                    Term::Unknown(_) => {}
+                }
+            }
+
+            for directive in directives {
+                let raw_term = directive.to_string();
+                if directive.mechanism.need_lookup() && !lookups.add() {
+                    return Evaluation::new(SpfResult::PermError).matched_by(raw_term);
+                }
+
+                let qualifier = directive.qualifier.as_ref().unwrap_or(&QualifierType::Pass);
+                let result = SpfResult::from(qualifier);
+                let nested_terms = match self
+                    .matches(ip, directive.mechanism, query, chain, lookups)
+                    .await
+                {
+                    Ok(None) => continue,
+                    Ok(Some(nested_terms)) => nested_terms,
+                    Err(evaluation) => return evaluation.matched_by(raw_term),
+                };
+
+                // The explanation is only looked up when it is used
+                let explanation = match (result, exp) {
+                    (SpfResult::Fail, Some(exp)) if explain => {
+                        self.resolver.resolve_exp_mut(exp, query).await.explanation
+                    }
+                    _ => None,
+                };
+
+                return Evaluation {
+                    result,
+                    matched_terms: nested_terms,
+                    explanation,
+                    error: None,
+                }
+                .matched_by(raw_term);
+            }
+
+            match redirect {
+                Some(Ok(r)) => {
+                    if !lookups.add() {
+                        return Evaluation::new(SpfResult::PermError).matched_by(&r.raw_value);
+                    }
+
+                    let evaluation = self
+                        .check_target(ip, &r.domain_spec, query, chain, lookups, explain)
+                        .await;
+                    match evaluation.result {
+                        SpfResult::None => Evaluation {
+                            result: SpfResult::PermError,
+                            ..evaluation
+                        },
+                        _ => evaluation,
+                    }
+                    .matched_by(&r.raw_value)
+                }
+                Some(Err(raw_term)) => Evaluation::new(SpfResult::PermError).matched_by(raw_term),
+                None => Evaluation::new(SpfResult::Neutral),
+            }
+        }
+        .boxed()
+    }
+
+    /// Fetch and evaluate the record of an included or redirected domain name
+    async fn check_target(
+        &self,
+        ip: &IpAddr,
+        domain_spec: &str,
+        query: &ResolveSpfQuery,
+        chain: &[String],
+        lookups: &mut Lookups,
+        explain: bool,
+    ) -> Evaluation {
+        let domain_name = self
+            .resolver
+            .target_domain_name(Some(domain_spec), query)
+            .await
+            .filter(|domain_name| is_valid_domain_name(domain_name));
+        // A malformed target has no record, a permerror for include and redirect
+        // (RFC 7208 sections 4.3, 5.2 and 6.1)
+        let Some(domain_name) = domain_name else {
+            return Evaluation::new(SpfResult::PermError)
+                .with_error(format!("'{}' is not a valid domain name", domain_spec));
+        };
+
+        let chain = [chain, &[chain_name(&domain_name)]].concat();
+        if chain[..chain.len() - 1].contains(chain.last().unwrap()) {
+            return Evaluation::new(SpfResult::PermError)
+                .with_error(SpfError::IncludeLoop { chain });
+        }
+
+        let target_query = ResolveSpfQuery {
+            domain_name,
+            record: None,
+            macro_context: query.macro_context.clone(),
+        };
+        match self.resolver.fetch_record(&target_query).await {
+            Ok(spf_record) => {
+                self.check_host(ip, spf_record, &target_query, &chain, lookups, explain)
+                    .await
+            }
+            // A target without a record is a permerror (RFC 7208 sections 5.2 and 6.1)
+            Err(err) => match *err {
+                SpfError::TempError(err) => Evaluation::new(SpfResult::TempError).with_error(err),
+                err => Evaluation::new(SpfResult::PermError).with_error(err),
+            },
+        }
+    }
+
+    /// Returns the nested matched terms if the mechanism matches the IP address
+    async fn matches(
+        &self,
+        ip: &IpAddr,
+        mechanism: Mechanism,
+        query: &ResolveSpfQuery,
+        chain: &[String],
+        lookups: &mut Lookups,
+    ) -> Result<Option<Vec<String>>, Evaluation> {
+        if let Mechanism::Include(m) = &mechanism {
+            let evaluation = self
+                .check_target(ip, &m.domain_spec, query, chain, lookups, false)
+                .await;
+            return match evaluation.result {
+                SpfResult::Pass => Ok(Some(evaluation.matched_terms)),
+                SpfResult::Fail | SpfResult::SoftFail | SpfResult::Neutral => Ok(None),
+                SpfResult::TempError => Err(evaluation),
+                SpfResult::PermError | SpfResult::None => Err(Evaluation {
+                    result: SpfResult::PermError,
+                    ..evaluation
+                }),
+            };
+        }
+
+        let mechanism = match self.resolver.resolve_mechanism(mechanism, query).await {
+            Ok(mechanism) => mechanism,
+            Err(err) => return Err(unresolved(err)),
+        };
+        if mechanism.is_void_lookup() && !lookups.add_void() {
+            return Err(Evaluation::new(SpfResult::PermError));
+        }
+
+        let is_match = match &mechanism {
+            Mechanism::All(_) => true,
+            Mechanism::Ip4(m) => in_network(&IpAddr::V4(m.ip_address), m.subnet_mask, ip),
+            Mechanism::Ip6(m) => in_network(&IpAddr::V6(m.ip_address), m.subnet_mask, ip),
+            Mechanism::A(m) => m.ip_addresses.iter().any(|address| {
+                let subnet_mask = dual_cidr_length(address, m.ip4_subnet_mask, m.ip6_subnet_mask);
+                in_network(address, subnet_mask, ip)
+            }),
+            Mechanism::Mx(m) if m.exceeds_host_limit() => {
+                return Err(Evaluation::new(SpfResult::PermError));
+            }
+            Mechanism::Mx(m) => m
+                .hosts
+                .iter()
+                .flat_map(|host| &host.ip_addresses)
+                .any(|address| {
+                    let subnet_mask =
+                        dual_cidr_length(address, m.ip4_subnet_mask, m.ip6_subnet_mask);
+                    in_network(address, subnet_mask, ip)
+                }),
+            Mechanism::Include(_) => false,
+            Mechanism::Exists(m) => !m.ip_addresses.is_empty(),
+            Mechanism::Ptr(m) => m.matches(),
+        };
+
+        Ok(is_match.then(Vec::new))
+    }
+}
+
+/// The `a` and `mx` mechanisms apply the CIDR length matching the address family
+fn dual_cidr_length(
+    address: &IpAddr,
+    ip4_subnet_mask: Option<u8>,
+    ip6_subnet_mask: Option<u8>,
+) -> Option<u8> {
+    match address {
+        IpAddr::V4(_) => ip4_subnet_mask,
+        IpAddr::V6(_) => ip6_subnet_mask,
+    }
+}
+
+/// Checks whether the IP address is part of the network defined by address and prefix length
+fn in_network(network: &IpAddr, prefix_length: Option<u8>, ip: &IpAddr) -> bool {
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let prefix_length = u32::from(prefix_length.unwrap_or(32).min(32));
+            let mask = u32::MAX.checked_shl(32 - prefix_length).unwrap_or(0);
+            u32::from(*network) & mask == u32::from(*ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let prefix_length = u32::from(prefix_length.unwrap_or(128).min(128));
+            let mask = u128::MAX.checked_shl(128 - prefix_length).unwrap_or(0);
+            u128::from(*network) & mask == u128::from(*ip) & mask
+        }
+        _ => false,
+    }
+}
+
+/// A DNS failure of the term is a temperror if retrying may help, anything else a permerror
+fn unresolved(err: DnsError) -> Evaluation {
+    let result = match err.is_temporary() {
+        true => SpfResult::TempError,
+        false => SpfResult::PermError,
+    };
+    Evaluation::new(result).with_error(err)
+}
+
+fn term_starts_with(term: &Term, prefix: &str) -> bool {
+    term_to_string(term)
+        .to_ascii_lowercase()
+        .starts_with(prefix)
+}
+
+fn term_to_string(term: &Term) -> String {
+    match term {
+        Term::Directive(d) => d.to_string(),
+        Term::Modifier(m) => m.to_string(),
+        Term::Unknown(u) => u.raw_rdata.to_owned(),
+        Term::Loop(l) => l.raw_rdata.to_owned(),
+    }
+}
+
+/// A multi-label domain name without empty or overlong labels (RFC 7208 section 4.3)
+fn is_valid_domain_name(domain_name: &str) -> bool {
+    let labels = domain_name
+        .strip_suffix('.')
+        .unwrap_or(domain_name)
+        .split('.')
+        .collect::<Vec<_>>();
+
+    labels.len() > 1
+        && labels
+            .iter()
+            .all(|label| !label.is_empty() && label.len() <= 63)
+}
+
+/// The sender identity defaults to "postmaster" at the given domain (RFC 7208 section 4.3)
+fn sender_identity(sender: Option<&str>, domain_name: &str) -> String {
+    match sender {
+        None | Some("") => format!("postmaster@{}", domain_name),
+        Some(sender) => match sender.split_once('@') {
+            None => format!("postmaster@{}", sender),
+            Some(("", domain)) => format!("postmaster@{}", domain),
+            Some(_) => sender.to_owned(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    use crate::dns::core::dns_resolver::MockDnsResolver;
+    use crate::dns::domain::{ARecord, AaaaRecord, DnsError, MxRecord, PtrRecord, TxtRecord};
+
+    struct EvaluationPresenter {
+        evaluation: Rc<RefCell<Option<SpfEvaluation>>>,
+    }
+
+    impl Presenter<SpfEvaluation, SpfError> for EvaluationPresenter {
+        fn success(&mut self, data: &SpfEvaluation) {
+            self.evaluation.replace(Some(SpfEvaluation {
+                domain_name: data.domain_name.to_owned(),
+                ip_address: data.ip_address,
+                sender: data.sender.to_owned(),
+                helo: data.helo.to_owned(),
+                result: data.result,
+                matched_terms: data.matched_terms.to_owned(),
+                explanation: data.explanation.to_owned(),
+                error: data.error.to_owned(),
+                lookup_count: data.lookup_count,
+                void_lookup_count: data.void_lookup_count,
+            }));
+        }
+
+        fn error(&mut self, _error: &SpfError) {}
+    }
+
+    async fn evaluate(
+        dns_resolver: &mut MockDnsResolver,
+        ip_address: &str,
+        record: Option<&str>,
+    ) -> SpfEvaluation {
+        let evaluation = Rc::new(RefCell::new(None));
+        let presenter = EvaluationPresenter {
+            evaluation: evaluation.clone(),
+        };
+
+        EvaluateSpfUseCaseImpl::new(dns_resolver)
+            .execute(
+                &EvaluateSpfQuery {
+                    domain_name: "example.com".to_owned(),
+                    ip_address: IpAddr::from_str(ip_address).unwrap(),
+                    sender: Some("user@example.com".to_owned()),
+                    helo: None,
+                    record: record.map(str::to_owned),
+                },
+                Box::new(presenter),
+            )
+            .await;
+
+        let evaluation = evaluation.take();
+        evaluation.expect("evaluation was not presented")
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_matching_ip4_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 ip4:203.0.113.0/24 -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+        assert_eq!(evaluation.matched_terms, vec!["ip4:203.0.113.0/24"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_for_not_matching_ip4_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "198.51.100.1",
+            Some("v=spf1 ip4:203.0.113.0/24 -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Fail);
+        assert_eq!(evaluation.matched_terms, vec!["-all"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_neutral_without_matching_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "2001:db8::1",
+            Some("v=spf1 ip6:2001:db9::/32"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Neutral);
+        assert!(evaluation.matched_terms.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_match_ipv4_mapped_ipv6_address() {
+        let mut dns_resolver = MockDnsResolver::new();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "::ffff:203.0.113.7",
+            Some("v=spf1 ip4:203.0.113.7 -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_none_without_spf_record() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(&mut dns_resolver, "203.0.113.7", None).await;
+
+        assert_eq!(evaluation.result, SpfResult::None);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_multiple_spf_records() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![
+                        vec!["v=spf1 +all".to_owned()],
+                        vec!["v=spf1 -all".to_owned()],
+                    ],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(&mut dns_resolver, "203.0.113.7", None).await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_include_loop() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1 include:example.com".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 include:_spf.example.com +all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+        assert_eq!(
+            evaluation.matched_terms,
+            vec!["include:_spf.example.com", "include:example.com"]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_be_temperror_for_a_failed_include_lookup() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |query| Err(DnsError::Timeout(query.domain_name.to_owned())));
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 include:_spf.example.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::TempError);
+        assert_eq!(evaluation.matched_terms, vec!["include:_spf.example.com"]);
+        assert_eq!(
+            evaluation.error.as_deref(),
+            Some("Query for '_spf.example.com' timed out")
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_an_invalid_target_name() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_a()
+            .once()
+            .return_once(move |query| Err(DnsError::InvalidName(query.domain_name.to_owned())));
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 a:exa..mple -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_an_invalid_include_target() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().never();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 include:exa..mple.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+        assert_eq!(evaluation.matched_terms, vec!["include:exa..mple.com"]);
+        assert_eq!(
+            evaluation.error.as_deref(),
+            Some("'exa..mple.com' is not a valid domain name")
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_unknown_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 ip4:203.0.113.7 foo -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+    }
+
+    #[tokio::test]
+    async fn it_should_ignore_unknown_modifier() {
+        let mut dns_resolver = MockDnsResolver::new();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 ip4:203.0.113.7 foo=bar -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_matching_include() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1 ip4:203.0.113.0/24 -all".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 include:_spf.example.com ~all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+        assert_eq!(
+            evaluation.matched_terms,
+            vec!["include:_spf.example.com", "ip4:203.0.113.0/24"]
+        );
+        assert_eq!(evaluation.lookup_count, 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_match_failing_include() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1 ip4:198.51.100.0/24 -all".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 include:_spf.example.com ~all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::SoftFail);
+        assert_eq!(evaluation.matched_terms, vec!["~all"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_include_without_spf_record() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 include:_spf.example.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+    }
+
+    #[tokio::test]
+    async fn it_should_use_redirect_without_matching_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1 -all".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 ip4:198.51.100.1 redirect=_spf.example.com"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Fail);
+        assert_eq!(
+            evaluation.matched_terms,
+            vec!["redirect=_spf.example.com", "-all"]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_matching_a_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_a().once().return_once(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("203.0.113.1").unwrap()],
+                ttl: 3600,
+            })
+        });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 a:example.com/24 -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+    }
+
+    #[tokio::test]
+    async fn it_should_apply_the_ip6_cidr_length_of_a_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+        // Only AAAA records are queried for an IPv6 sender
+        dns_resolver
+            .expect_query_aaaa()
+            .once()
+            .return_once(move |_| {
+                Ok(AaaaRecord {
+                    ip_addresses: vec![Ipv6Addr::from_str("2001:db8::1").unwrap()],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "2001:db8::ff",
+            Some("v=spf1 a/32//64 -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_matching_mx_mechanism() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_mx().once().return_once(move |_| {
+            Ok(MxRecord {
+                exchanges: vec!["mail.example.com".to_owned()],
+                ttl: 3600,
+            })
+        });
+        dns_resolver.expect_query_a().once().return_once(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("203.0.113.7").unwrap()],
+                ttl: 3600,
+            })
+        });
+
+        let evaluation = evaluate(&mut dns_resolver, "203.0.113.7", Some("v=spf1 mx -all")).await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_more_than_ten_mx_hosts() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_mx().once().return_once(move |_| {
+            Ok(MxRecord {
+                exchanges: (0..11).map(|i| format!("mx{}.example.com", i)).collect(),
+                ttl: 3600,
+            })
+        });
+        dns_resolver.expect_query_a().never();
+
+        let evaluation = evaluate(&mut dns_resolver, "203.0.113.7", Some("v=spf1 mx -all")).await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+        assert_eq!(evaluation.matched_terms, vec!["mx"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_when_lookup_limit_is_exceeded() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_a().times(10).returning(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("198.51.100.1").unwrap()],
+                ttl: 3600,
+            })
+        });
+        let record = format!("v=spf1 {} -all", ["a"; 11].join(" "));
+
+        let evaluation = evaluate(&mut dns_resolver, "203.0.113.7", Some(&record)).await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_dns_failure_outcome_for_temperror() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |query| Err(DnsError::ServFail(query.domain_name.to_owned())));
+        let presenter = EvaluationPresenter {
+            evaluation: Rc::new(RefCell::new(None)),
+        };
+
+        let outcome = EvaluateSpfUseCaseImpl::new(&dns_resolver)
+            .execute(
+                &EvaluateSpfQuery {
+                    domain_name: "example.com".to_owned(),
+                    ip_address: IpAddr::from_str("203.0.113.7").unwrap(),
+                    sender: None,
+                    helo: None,
+                    record: None,
+                },
+                Box::new(presenter),
+            )
+            .await;
+
+        assert_eq!(outcome, Outcome::DnsFailure);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_resolve_terms_after_a_match() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_a().never();
+        dns_resolver.expect_query_txt().never();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 ip4:203.0.113.7 a:mail.example.com include:_spf.example.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+        assert_eq!(evaluation.lookup_count, 0);
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_nested_includes_at_the_lookup_limit() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .times(10)
+            .returning(move |query| {
+                let includes = (0..10)
+                    .map(|i| format!("include:{}.{}", i, query.domain_name))
+                    .collect::<Vec<_>>();
+                Ok(TxtRecord {
+                    records: vec![vec![format!("v=spf1 {} -all", includes.join(" "))]],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 include:_spf.example.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+        assert_eq!(evaluation.lookup_count, 11);
+    }
+
+    #[tokio::test]
+    async fn it_should_expand_macros_with_the_sender_ip_address() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_a()
+            .once()
+            .withf(|query| query.domain_name == "7.113.0.203.in-addr._spf.example.com")
+            .return_once(move |_| {
+                Ok(ARecord {
+                    ip_addresses: vec![Ipv4Addr::from_str("203.0.113.7").unwrap()],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 a:%{ir}.%{v}._spf.%{d} -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_existing_exists_domain() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_a()
+            .once()
+            .withf(|query| query.domain_name == "user.203.0.113.7._spf.example.com")
+            .return_once(move |_| {
+                Ok(ARecord {
+                    ip_addresses: vec![Ipv4Addr::from_str("127.0.0.2").unwrap()],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 exists:%{l}.%{i}._spf.%{d} -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+        assert_eq!(evaluation.lookup_count, 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_when_void_lookup_limit_is_exceeded() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_a().times(3).returning(|_| {
+            Ok(ARecord {
+                ip_addresses: vec![],
+                ttl: 3600,
+            })
+        });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 exists:a.example.com exists:b.example.com exists:c.example.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+        assert_eq!(evaluation.void_lookup_count, 3);
+        assert_eq!(evaluation.matched_terms, vec!["exists:c.example.com"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_allow_two_void_lookups() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_a().times(2).returning(|_| {
+            Ok(ARecord {
+                ip_addresses: vec![],
+                ttl: 3600,
+            })
+        });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 exists:a.example.com exists:b.example.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Fail);
+        assert_eq!(evaluation.void_lookup_count, 2);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_match_missing_exists_domain() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_a().once().return_once(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![],
+                ttl: 3600,
+            })
+        });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 exists:%{i}._spf.%{d} -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Fail);
+    }
+
+    #[tokio::test]
+    async fn it_should_pass_for_validated_ptr_host_name() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_ptr()
+            .once()
+            .return_once(move |_| {
+                Ok(PtrRecord {
+                    names: vec!["mail.example.com.".to_owned()],
+                    ttl: 3600,
+                })
+            });
+        dns_resolver.expect_query_a().once().return_once(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("203.0.113.7").unwrap()],
+                ttl: 3600,
+            })
+        });
+
+        let evaluation = evaluate(&mut dns_resolver, "203.0.113.7", Some("v=spf1 ptr -all")).await;
+
+        assert_eq!(evaluation.result, SpfResult::Pass);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_match_ptr_host_name_of_other_domain() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_ptr()
+            .once()
+            .return_once(move |_| {
+                Ok(PtrRecord {
+                    names: vec!["mail.example.org.".to_owned()],
+                    ttl: 3600,
+                })
+            });
+        dns_resolver.expect_query_a().once().return_once(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("203.0.113.7").unwrap()],
+                ttl: 3600,
+            })
+        });
+
+        let evaluation = evaluate(&mut dns_resolver, "203.0.113.7", Some("v=spf1 ptr -all")).await;
+
+        assert_eq!(evaluation.result, SpfResult::Fail);
+    }
+
+    #[tokio::test]
+    async fn it_should_explain_a_failing_result() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["%{i} is not allowed to send mail as %{s}".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "198.51.100.1",
+            Some("v=spf1 ip4:203.0.113.7 -all exp=explain.%{d}"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::Fail);
+        assert_eq!(
+            evaluation.explanation.unwrap(),
+            "198.51.100.1 is not allowed to send mail as user@example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_multiple_exp_modifiers() {
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().never();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 -all exp=a.example.com exp=b.example.com"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+        assert_eq!(evaluation.matched_terms, vec!["exp=b.example.com"]);
+    }
+
+    #[tokio::test]
+    async fn it_should_be_permerror_for_invalid_macro() {
+        let mut dns_resolver = MockDnsResolver::new();
+
+        let evaluation = evaluate(
+            &mut dns_resolver,
+            "203.0.113.7",
+            Some("v=spf1 ip4:203.0.113.7 include:%{z}.example.com -all"),
+        )
+        .await;
+
+        assert_eq!(evaluation.result, SpfResult::PermError);
+        assert_eq!(evaluation.matched_terms, vec!["include:%{z}.example.com"]);
+    }
+
+    #[test]
+    fn it_should_default_sender_to_postmaster() {
+        assert_eq!(
+            sender_identity(None, "example.com"),
+            "postmaster@example.com"
+        );
+        assert_eq!(
+            sender_identity(Some("@example.org"), "example.com"),
+            "postmaster@example.org"
+        );
+        assert_eq!(
+            sender_identity(Some("user@example.org"), "example.com"),
+            "user@example.org"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub mod check;
+pub mod evaluate;
 mod resolver;
 
 pub(in crate::spf) use resolver::use_case::ResolveSpfUseCaseImpl;
@@ -1,13 +1,33 @@
-use std::str::FromStr;
+use std::net::IpAddr;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use crate::dns::core::dns_resolver::{ARecordQuery, DnsResolver, MxRecordQuery, TxtRecordQuery};
+use async_trait::async_trait;
+use futures::future::{join, join_all, BoxFuture, FutureExt};
+
+use crate::dns::core::dns_resolver::{
+    ARecordQuery, AaaaRecordQuery, DnsResolver, MxRecordQuery, PtrRecordQuery, TxtRecordQuery,
+};
+use crate::dns::domain::DnsError;
 use crate::spf::domain::{
-    AMechanism, AllMechanism, Directive, IncludeMechanism, Ip4Mechanism, Ip6Mechanism, Mechanism,
-    Modifier, MxMechanism, QualifierType, RedirectModifier, SpfError, Term, Version,
+    AMechanism, Directive, ExistsMechanism, ExpModifier, IncludeMechanism, LoopTerm, MacroContext,
+    MacroLetter, MacroString, Mechanism, Modifier, MxHost, MxMechanism, PtrMechanism,
+    RedirectModifier, SpfError, SpfRecord, Term, UnknownTerm,
 };
 
+/// Maximum number of nested includes and redirects that are resolved
+pub const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// Maximum number of terms that cause DNS queries per record (RFC 7208 section 4.6.4)
+pub const MAX_LOOKUP_COUNT: usize = 10;
+
+/// Maximum number of DNS queries per record that return no answer (RFC 7208 section 4.6.4)
+pub const MAX_VOID_LOOKUP_COUNT: usize = 2;
+
+#[async_trait]
 pub trait ResolveSpfUseCase {
-    fn resolve(&mut self, query: &ResolveSpfQuery) -> Result<SpfAnswer, Box<SpfError>>;
+    /// Fetch (unless given), parse and resolve the SPF record of a domain name.
+    async fn resolve(&self, query: &ResolveSpfQuery) -> Result<SpfRecord, Box<SpfError>>;
 }
 
 pub struct ResolveSpfQuery {
@@ -16,291 +36,714 @@ pub struct ResolveSpfQuery {
 
     /// The SPF record to parse. If not provided, the record will be fetched from DNS.
     pub record: Option<String>,
+
+    /// The values used to expand macros. Without a context only the `%{d}` macro is expanded.
+    pub macro_context: Option<MacroContext>,
 }
 
-pub struct SpfAnswer {
-    /// The version of the SPF record (e.g. "spf1")
-    pub version: Version,
+/// Resolves the terms of a record concurrently, nested records are resolved depth-first
+pub struct ResolveSpfUseCaseImpl<'a> {
+    pub(crate) dns_resolver: &'a dyn DnsResolver,
+}
 
-    /// The list of directives or modifiers
-    pub terms: Vec<Term>,
+impl<'a> ResolveSpfUseCaseImpl<'a> {
+    pub fn new(dns_resolver: &'a dyn DnsResolver) -> Self {
+        ResolveSpfUseCaseImpl { dns_resolver }
+    }
+}
 
-    /// RDATA of a single DNS TXT resource record
-    pub raw_rdata: String,
+#[async_trait]
+impl<'a> ResolveSpfUseCase for ResolveSpfUseCaseImpl<'a> {
+    async fn resolve(&self, query: &ResolveSpfQuery) -> Result<SpfRecord, Box<SpfError>> {
+        self.resolve_in_chain(query, &[], &LookupBudget::default())
+            .await
+    }
 }
 
-pub struct ResolveSpfUseCaseImpl<'a> {
-    pub(crate) dns_resolver: &'a mut dyn DnsResolver,
+/// The lookups left for a record and all of its nested records
+struct LookupBudget(AtomicUsize);
+
+impl Default for LookupBudget {
+    fn default() -> Self {
+        LookupBudget(AtomicUsize::new(MAX_LOOKUP_COUNT))
+    }
+}
+
+impl LookupBudget {
+    /// Takes one lookup, returns false if none is left
+    fn take(&self) -> bool {
+        self.0
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                left.checked_sub(1)
+            })
+            .is_ok()
+    }
 }
 
 impl<'a> ResolveSpfUseCaseImpl<'a> {
-    pub fn new(query_txt_record_gateway: &'a mut dyn DnsResolver) -> Self {
-        ResolveSpfUseCaseImpl {
-            dns_resolver: query_txt_record_gateway,
+    /// Fetch and resolve the record of a domain name included or redirected by the chain of
+    /// domain names (outermost first).
+    ///
+    /// The lookups of all nested records are taken from the same budget, terms beyond it are
+    /// not resolved. The future is boxed as nested records are resolved recursively.
+    fn resolve_in_chain<'b>(
+        &'b self,
+        query: &'b ResolveSpfQuery,
+        chain: &'b [String],
+        budget: &'b LookupBudget,
+    ) -> BoxFuture<'b, Result<SpfRecord, Box<SpfError>>> {
+        async move {
+            let mut chain = chain.to_vec();
+            let domain_name = chain_name(&query.domain_name);
+            if chain.contains(&domain_name) {
+                chain.push(domain_name);
+                return Err(Box::new(SpfError::IncludeLoop { chain }));
+            }
+            if chain.len() > MAX_INCLUDE_DEPTH {
+                chain.push(domain_name);
+                return Err(Box::new(SpfError::IncludeDepthExceeded { chain }));
+            }
+
+            let record = self.fetch_record(query).await?;
+
+            chain.push(domain_name);
+            Ok(self.resolve_terms(record, query, &chain, budget).await)
         }
+        .boxed()
     }
-}
 
-impl<'a> ResolveSpfUseCase for ResolveSpfUseCaseImpl<'a> {
-    fn resolve(&mut self, query: &ResolveSpfQuery) -> Result<SpfAnswer, Box<SpfError>> {
+    /// Fetch (unless given) and parse the record of a domain name without resolving its terms
+    pub(crate) async fn fetch_record(
+        &self,
+        query: &ResolveSpfQuery,
+    ) -> Result<SpfRecord, Box<SpfError>> {
         let spf_rdata = match &query.record {
-            Some(rdata) => Some(rdata.clone()),
+            Some(rdata) => vec![rdata.clone()],
             None => {
                 let result = self
                     .dns_resolver
                     .query_txt(&TxtRecordQuery {
                         domain_name: query.domain_name.clone(),
                     })
-                    .expect("query txt record");
-
-                result
-                    .records
-                    .iter()
-                    .map(|record| record.to_string())
-                    .find(|line| line.starts_with("v=spf1"))
+                    .await;
+
+                match result {
+                    Ok(txt_record) => txt_record
+                        .texts()
+                        .into_iter()
+                        .filter(|text| SpfRecord::is_spf_record(text))
+                        .collect(),
+                    // A domain without TXT records has no SPF record (RFC 7208 section 4.5)
+                    Err(err) if err.is_void() => vec![],
+                    Err(err) => return Err(Box::new(err.into())),
+                }
             }
         };
 
-        if spf_rdata.is_none() {
-            return Err(Box::new(SpfError::NoSpfRecordFound(format!(
+        // A domain must not publish more than one SPF record (RFC 7208 section 3.2)
+        match spf_rdata.as_slice() {
+            [] => Err(Box::new(SpfError::NoSpfRecordFound(format!(
                 "No SPF record found for '{}'",
                 query.domain_name
-            ))));
+            )))),
+            [rdata] => Ok(SpfRecord::parse(rdata)),
+            _ => Err(Box::new(SpfError::MultipleSpfRecordsFound {
+                domain_name: query.domain_name.to_owned(),
+                records: spf_rdata,
+            })),
         }
+    }
 
-        let raw_rdata = spf_rdata.unwrap();
-        let mut rdata_parts = raw_rdata.split(' ');
-
-        let version = Version::from_str(rdata_parts.next().unwrap()).unwrap();
-        let terms = rdata_parts
-            .map(|term| {
-                let (qualifier, mechanism_str) = if term.starts_with(QualifierType::Pass.as_str()) {
-                    (Some(QualifierType::Pass), &term[1..])
-                } else if term.starts_with(QualifierType::Fail.as_str()) {
-                    (Some(QualifierType::Fail), &term[1..])
-                } else if term.starts_with(QualifierType::SoftFail.as_str()) {
-                    (Some(QualifierType::SoftFail), &term[1..])
-                } else if term.starts_with(QualifierType::Neutral.as_str()) {
-                    (Some(QualifierType::Neutral), &term[1..])
-                } else {
-                    (None, term)
-                };
+    /// Resolve the terms of the record in evaluation order, the directives from left to right and
+    /// the redirect last (RFC 7208 section 4.6).
+    ///
+    /// Each term that causes a lookup takes it from the budget before the next one, nested records
+    /// are resolved depth-first. The other lookups of the record run concurrently.
+    async fn resolve_terms(
+        &self,
+        record: SpfRecord,
+        query: &ResolveSpfQuery,
+        chain: &[String],
+        budget: &LookupBudget,
+    ) -> SpfRecord {
+        let SpfRecord {
+            version,
+            terms,
+            raw_rdata,
+        } = record;
+
+        let (directives, modifiers): (Vec<_>, Vec<_>) =
+            (0..terms.len()).partition(|&index| matches!(terms[index], Term::Directive(_)));
+        let mut terms = terms.into_iter().map(Some).collect::<Vec<_>>();
+        let mut is_resolved = vec![false; terms.len()];
+        for index in directives.into_iter().chain(modifiers) {
+            let Some(term) = terms[index].take() else {
+                continue;
+            };
+            let need_lookup = match &term {
+                Term::Directive(d) => d.mechanism.need_lookup(),
+                Term::Modifier(m) => m.need_lookup(),
+                _ => false,
+            };
+            let is_nested_record = matches!(
+                term,
+                Term::Directive(Directive {
+                    mechanism: Mechanism::Include(_),
+                    ..
+                }) | Term::Modifier(Modifier::Redirect(_))
+            );
+
+            let term = if need_lookup && !budget.take() {
+                lookup_limit_term(&raw_rdata, term.span())
+            } else if is_nested_record {
+                self.resolve_term(term, &raw_rdata, query, chain, budget)
+                    .await
+            } else {
+                terms[index] = Some(term);
+                continue;
+            };
+            is_resolved[index] = true;
+            terms[index] = Some(term);
+        }
 
-                match mechanism_str {
-                    mechanism_str if mechanism_str.starts_with("include:") => {
-                        self.to_include_term_mut(qualifier, mechanism_str)
-                    }
-                    mechanism_str if mechanism_str == "a" || mechanism_str.starts_with("a:") => {
-                        self.to_a_term_mut(
-                            qualifier,
-                            mechanism_str,
-                            query.domain_name.clone().as_str(),
-                        )
-                    }
-                    mechanism_str if mechanism_str == "mx" || mechanism_str.starts_with("mx:") => {
-                        self.to_mx_term_mut(
-                            qualifier,
-                            mechanism_str,
-                            query.domain_name.clone().as_str(),
-                        )
-                    }
-                    mechanism_str if mechanism_str.starts_with("ip4:") => {
-                        self.to_ipv4_term(qualifier, mechanism_str)
-                    }
-                    mechanism_str if mechanism_str.starts_with("ip6:") => {
-                        self.to_ipv6_term(qualifier, mechanism_str)
-                    }
-                    mechanism_str if mechanism_str == "all" => {
-                        self.to_all(qualifier, mechanism_str)
-                    }
-                    mechanism_str if mechanism_str.starts_with("redirect=") => {
-                        self.to_redirect_term_mut(mechanism_str)
-                    }
-                    _ => Term::new_unknown(term, None),
+        let raw = &raw_rdata;
+        let terms = join_all(terms.into_iter().flatten().zip(is_resolved).map(
+            |(term, is_resolved)| async move {
+                match is_resolved {
+                    true => term,
+                    false => self.resolve_term(term, raw, query, chain, budget).await,
                 }
-            })
-            .collect::<Vec<_>>();
+            },
+        ))
+        .await;
 
-        Ok(SpfAnswer {
-            raw_rdata,
+        SpfRecord {
             version,
             terms,
-        })
+            raw_rdata,
+        }
     }
-}
 
-impl<'a> ResolveSpfUseCaseImpl<'a> {
-    fn to_a_term_mut(
-        &mut self,
-        qualifier: Option<QualifierType>,
-        term: &str,
-        domain_name: &str,
+    /// Fill in the DNS data of a parsed term
+    async fn resolve_term(
+        &self,
+        term: Term,
+        raw_rdata: &str,
+        query: &ResolveSpfQuery,
+        chain: &[String],
+        budget: &LookupBudget,
     ) -> Term {
-        let (_, domain_name) = term.split_once(':').unwrap_or((term, domain_name));
-        let (domain_name, subnet_mask) = domain_name.split_once('/').unwrap_or((domain_name, ""));
+        let span = term.span();
+        let unresolved = |err| unresolved_term(err, &raw_rdata[span.clone()], span.clone());
+
+        match term {
+            Term::Directive(directive) => {
+                let Directive {
+                    qualifier,
+                    mechanism,
+                    span,
+                } = directive;
+                let mechanism = match mechanism {
+                    Mechanism::Include(include) => {
+                        match self
+                            .resolve_include_mut(include, query, chain, budget)
+                            .await
+                        {
+                            Ok(include) => Ok(Mechanism::Include(include)),
+                            Err(err) => return unresolved(*err),
+                        }
+                    }
+                    mechanism => self.resolve_mechanism(mechanism, query).await,
+                };
+                let mechanism = match mechanism {
+                    Ok(mechanism) => mechanism,
+                    Err(err) => return unresolved(err.into()),
+                };
 
-        let a_record = self.dns_resolver.query_a(&ARecordQuery {
-            domain_name: domain_name.to_string(),
-        });
-        let record = a_record.unwrap();
-
-        Term::Directive(Directive {
-            qualifier,
-            mechanism: Mechanism::A(AMechanism {
-                raw_value: term.to_string(),
-                ip_addresses: record.ip_addresses,
-                subnet_mask: subnet_mask.parse().ok(),
-            }),
+                Term::Directive(Directive {
+                    qualifier,
+                    mechanism,
+                    span,
+                })
+            }
+            Term::Modifier(Modifier::Redirect(redirect)) => {
+                match self
+                    .resolve_redirect_mut(redirect, query, chain, budget)
+                    .await
+                {
+                    Ok(redirect) => Term::Modifier(Modifier::Redirect(redirect)),
+                    Err(err) => unresolved(*err),
+                }
+            }
+            Term::Modifier(Modifier::Exp(exp)) => {
+                Term::Modifier(Modifier::Exp(self.resolve_exp_mut(exp, query).await))
+            }
+            term => term,
+        }
+    }
+
+    /// Fill in the DNS data a mechanism is matched against, included records are not resolved
+    pub(crate) async fn resolve_mechanism(
+        &self,
+        mechanism: Mechanism,
+        query: &ResolveSpfQuery,
+    ) -> Result<Mechanism, DnsError> {
+        Ok(match mechanism {
+            Mechanism::A(a) => Mechanism::A(self.resolve_a_mut(a, query).await?),
+            Mechanism::Mx(mx) => Mechanism::Mx(self.resolve_mx_mut(mx, query).await?),
+            Mechanism::Exists(exists) => {
+                Mechanism::Exists(self.resolve_exists_mut(exists, query).await?)
+            }
+            Mechanism::Ptr(ptr) => Mechanism::Ptr(self.resolve_ptr_mut(ptr, query).await),
+            mechanism => mechanism,
         })
     }
 
-    fn to_mx_term_mut(
-        &mut self,
-        qualifier: Option<QualifierType>,
-        term: &str,
+    async fn resolve_a_mut(
+        &self,
+        mut a: AMechanism,
+        query: &ResolveSpfQuery,
+    ) -> Result<AMechanism, DnsError> {
+        a.domain_name = self
+            .target_domain_name(a.domain_spec.as_deref(), query)
+            .await;
+        if let Some(domain_name) = &a.domain_name {
+            a.ip_addresses = self
+                .ip_addresses(domain_name, sender_ip_address(query))
+                .await?;
+        }
+
+        Ok(a)
+    }
+
+    async fn resolve_mx_mut(
+        &self,
+        mut mx: MxMechanism,
+        query: &ResolveSpfQuery,
+    ) -> Result<MxMechanism, DnsError> {
+        mx.domain_name = self
+            .target_domain_name(mx.domain_spec.as_deref(), query)
+            .await;
+        if let Some(domain_name) = &mx.domain_name {
+            let mx_record = self
+                .dns_resolver
+                .query_mx(&MxRecordQuery {
+                    domain_name: domain_name.to_owned(),
+                })
+                .await;
+            let exchanges = match mx_record {
+                Ok(mx_record) => mx_record.exchanges,
+                Err(err) if err.is_void() => vec![],
+                Err(err) => return Err(err),
+            };
+
+            // The hosts are not resolved if there are too many, the mechanism is a permerror anyway
+            let names = match exchanges.len() {
+                len if len > MxMechanism::MAX_HOSTS => &[][..],
+                _ => &exchanges[..],
+            };
+            let mut host_ip_addresses = join_all(
+                names
+                    .iter()
+                    .map(|name| self.ip_addresses(name, sender_ip_address(query))),
+            )
+            .await
+            .into_iter();
+
+            mx.hosts = Vec::with_capacity(exchanges.len());
+            for name in exchanges {
+                let ip_addresses = host_ip_addresses.next().transpose()?.unwrap_or_default();
+                mx.hosts.push(MxHost { name, ip_addresses });
+            }
+        }
+
+        Ok(mx)
+    }
+
+    /// The A and AAAA addresses of a host, limited to the address family of the sender if known.
+    ///
+    /// A host without addresses is not an error, it counts as a void lookup.
+    async fn ip_addresses(
+        &self,
         domain_name: &str,
-    ) -> Term {
-        let (_, domain_name) = term.split_once(':').unwrap_or((term, domain_name));
-        let (domain_name, subnet_mask) = domain_name.split_once('/').unwrap_or((domain_name, ""));
+        sender: Option<IpAddr>,
+    ) -> Result<Vec<IpAddr>, DnsError> {
+        let query_a = async {
+            match sender {
+                Some(IpAddr::V6(_)) => None,
+                _ => Some(
+                    self.dns_resolver
+                        .query_a(&ARecordQuery {
+                            domain_name: domain_name.to_owned(),
+                        })
+                        .await,
+                ),
+            }
+        };
+        let query_aaaa = async {
+            match sender {
+                Some(IpAddr::V4(_)) => None,
+                _ => Some(
+                    self.dns_resolver
+                        .query_aaaa(&AaaaRecordQuery {
+                            domain_name: domain_name.to_owned(),
+                        })
+                        .await,
+                ),
+            }
+        };
+        let (a_record, aaaa_record) = join(query_a, query_aaaa).await;
 
-        let a_record = self.dns_resolver.query_mx(&MxRecordQuery {
-            domain_name: domain_name.to_string(),
-        });
-        let record = a_record.unwrap();
-
-        Term::Directive(Directive {
-            qualifier,
-            mechanism: Mechanism::Mx(MxMechanism {
-                raw_value: term.to_string(),
-                hosts: record.exchanges,
-                subnet_mask: subnet_mask.parse().ok(),
-            }),
-        })
+        let mut ip_addresses = vec![];
+        match a_record {
+            Some(Ok(a_record)) => {
+                ip_addresses.extend(a_record.ip_addresses.into_iter().map(IpAddr::V4))
+            }
+            Some(Err(err)) if err.is_void() => log::debug!("{}", err),
+            Some(Err(err)) => return Err(err),
+            None => {}
+        }
+        match aaaa_record {
+            Some(Ok(aaaa_record)) => {
+                ip_addresses.extend(aaaa_record.ip_addresses.into_iter().map(IpAddr::V6))
+            }
+            Some(Err(err)) if err.is_void() => log::debug!("{}", err),
+            Some(Err(err)) => return Err(err),
+            None => {}
+        }
+
+        Ok(ip_addresses)
+    }
+
+    async fn resolve_exists_mut(
+        &self,
+        mut exists: ExistsMechanism,
+        query: &ResolveSpfQuery,
+    ) -> Result<ExistsMechanism, DnsError> {
+        exists.domain_name = self
+            .target_domain_name(Some(&exists.domain_spec), query)
+            .await;
+        if let Some(domain_name) = &exists.domain_name {
+            let a_record = self
+                .dns_resolver
+                .query_a(&ARecordQuery {
+                    domain_name: domain_name.to_owned(),
+                })
+                .await;
+            // exists only queries A records, even for IPv6 connections
+            exists.ip_addresses = match a_record {
+                Ok(a_record) => a_record.ip_addresses.into_iter().map(IpAddr::V4).collect(),
+                Err(err) if err.is_void() => vec![],
+                Err(err) => return Err(err),
+            };
+        }
+
+        Ok(exists)
+    }
+
+    async fn resolve_ptr_mut(
+        &self,
+        mut ptr: PtrMechanism,
+        query: &ResolveSpfQuery,
+    ) -> PtrMechanism {
+        ptr.domain_name = self
+            .target_domain_name(ptr.domain_spec.as_deref(), query)
+            .await;
+        // The host names can only be validated for the IP address of a sender
+        ptr.validated_names = match (&ptr.domain_name, &query.macro_context) {
+            (Some(_), Some(context)) => Some(self.validated_names(&context.ip_address).await),
+            _ => None,
+        };
+
+        ptr
     }
 
-    fn to_include_term_mut(&mut self, qualifier: Option<QualifierType>, term: &str) -> Term {
-        let (_, sub_domain_name) = term.split_once(':').unwrap_or((term, ""));
+    /// Resolve the included record or return why it could not be resolved
+    async fn resolve_include_mut(
+        &self,
+        mut include: IncludeMechanism,
+        query: &ResolveSpfQuery,
+        chain: &[String],
+        budget: &LookupBudget,
+    ) -> Result<IncludeMechanism, Box<SpfError>> {
+        let Some(domain_name) = self
+            .target_domain_name(Some(&include.domain_spec), query)
+            .await
+        else {
+            return Ok(include);
+        };
 
-        let spf_summary = self.resolve(&ResolveSpfQuery {
-            domain_name: sub_domain_name.to_string(),
+        let include_query = ResolveSpfQuery {
+            domain_name,
             record: None,
-        });
+            macro_context: query.macro_context.clone(),
+        };
+        let spf = self.resolve_in_chain(&include_query, chain, budget).await?;
 
-        match spf_summary {
-            Err(err) => match err.as_ref() {
-                SpfError::NoSpfRecordFound(err) => Term::new_unknown(term, Some(err.to_string())),
-                _ => unreachable!(),
-            },
-            Ok(spf) => Term::Directive(Directive {
-                qualifier,
-                mechanism: Mechanism::Include(IncludeMechanism {
-                    raw_value: term.to_string(),
-                    version: spf.version,
-                    domain_spec: sub_domain_name.to_string(),
-                    terms: spf.terms,
-                    raw_rdata: spf.raw_rdata,
-                }),
-            }),
-        }
+        include.version = spf.version;
+        include.terms = spf.terms;
+        include.raw_rdata = spf.raw_rdata;
+        Ok(include)
     }
 
-    fn to_redirect_term_mut(&mut self, term: &str) -> Term {
-        let (_, domain_name) = term.split_once('=').unwrap_or((term, ""));
+    /// Resolve the redirected record or return why it could not be resolved
+    async fn resolve_redirect_mut(
+        &self,
+        mut redirect: RedirectModifier,
+        query: &ResolveSpfQuery,
+        chain: &[String],
+        budget: &LookupBudget,
+    ) -> Result<RedirectModifier, Box<SpfError>> {
+        let Some(domain_name) = self
+            .target_domain_name(Some(&redirect.domain_spec), query)
+            .await
+        else {
+            return Ok(redirect);
+        };
 
-        let spf_summary = self.resolve(&ResolveSpfQuery {
-            domain_name: domain_name.to_string(),
+        let redirect_query = ResolveSpfQuery {
+            domain_name,
             record: None,
-        });
+            macro_context: query.macro_context.clone(),
+        };
+        let spf = self
+            .resolve_in_chain(&redirect_query, chain, budget)
+            .await?;
+
+        redirect.version = spf.version;
+        redirect.terms = spf.terms;
+        redirect.raw_rdata = spf.raw_rdata;
+        Ok(redirect)
+    }
 
-        match spf_summary {
-            Err(err) => match err.as_ref() {
-                SpfError::NoSpfRecordFound(err) => Term::new_unknown(term, Some(err.to_string())),
-                _ => unreachable!(),
-            },
-            Ok(spf) => Term::Modifier(Modifier::Redirect(RedirectModifier {
-                raw_value: term.to_string(),
-                version: spf.version,
-                domain_spec: domain_name.to_string(),
-                terms: spf.terms,
-                raw_rdata: spf.raw_rdata,
-            })),
+    /// Expand the domain-spec or fall back to the current domain name.
+    ///
+    /// Returns `None` if the domain-spec is invalid or depends on the sender.
+    pub(crate) async fn target_domain_name(
+        &self,
+        domain_spec: Option<&str>,
+        query: &ResolveSpfQuery,
+    ) -> Option<String> {
+        let Some(domain_spec) = domain_spec else {
+            return Some(query.domain_name.to_owned());
+        };
+
+        let macro_string = MacroString::parse(domain_spec).ok()?;
+        let macro_context = self.macro_context(&macro_string, query).await;
+
+        let domain_name =
+            macro_string.expand_domain_spec(&query.domain_name, macro_context.as_ref());
+        if domain_name.is_none() {
+            log::debug!("Skip lookup of '{}' without sender", domain_spec);
         }
+
+        domain_name
     }
 
-    fn to_ipv4_term(&self, qualifier: Option<QualifierType>, term: &str) -> Term {
-        let (_, ip_address) = term.split_once(':').unwrap_or((term, ""));
-        let (ip_address, subnet_mask) = ip_address.split_once('/').unwrap_or((ip_address, ""));
+    /// Expand the macros of an explanation TXT record (RFC 7208 section 6.2)
+    async fn explanation(&self, explain_string: &str, query: &ResolveSpfQuery) -> Option<String> {
+        let macro_string = MacroString::parse_explain_string(explain_string).ok()?;
+        let macro_context = self.macro_context(&macro_string, query).await?;
 
-        Term::Directive(Directive {
-            qualifier,
-            mechanism: Mechanism::Ip4(Ip4Mechanism {
-                raw_value: term.to_string(),
-                ip_address: ip_address.parse().expect("IPv4 address"),
-                subnet_mask: subnet_mask.parse().ok(),
-            }),
-        })
+        macro_string.expand(&query.domain_name, Some(&macro_context))
     }
-    fn to_ipv6_term(&self, qualifier: Option<QualifierType>, term: &str) -> Term {
-        let (_, ip_address) = term.split_once(':').unwrap_or((term, ""));
-        let (ip_address, subnet_mask) = ip_address.split_once('/').unwrap_or((ip_address, ""));
-
-        Term::Directive(Directive {
-            qualifier,
-            mechanism: Mechanism::Ip6(Ip6Mechanism {
-                raw_value: term.to_string(),
-                ip_address: ip_address.parse().expect("IPv6 address"),
-                subnet_mask: subnet_mask.parse().ok(),
-            }),
-        })
+
+    async fn macro_context(
+        &self,
+        macro_string: &MacroString,
+        query: &ResolveSpfQuery,
+    ) -> Option<MacroContext> {
+        match &query.macro_context {
+            // The validated domain name needs additional lookups and is only resolved when used
+            Some(context) if macro_string.has_letter(MacroLetter::ValidatedDomain) => {
+                let mut context = context.clone();
+                context.validated_domain = self
+                    .validated_domain(&context.ip_address, &query.domain_name)
+                    .await;
+                Some(context)
+            }
+            context => context.clone(),
+        }
     }
-    fn to_all(&self, qualifier: Option<QualifierType>, term: &str) -> Term {
-        Term::Directive(Directive {
-            qualifier,
-            mechanism: Mechanism::All(AllMechanism {
-                raw_value: term.to_string(),
-            }),
-        })
+
+    /// Host names of the IP address that resolve back to it (RFC 7208 section 5.5)
+    async fn validated_names(&self, ip_address: &IpAddr) -> Vec<String> {
+        let Ok(ptr_record) = self
+            .dns_resolver
+            .query_ptr(&PtrRecordQuery {
+                ip_address: *ip_address,
+            })
+            .await
+        else {
+            return vec![];
+        };
+
+        let names = ptr_record
+            .names
+            .into_iter()
+            .take(PtrMechanism::MAX_NAMES)
+            .map(|name| name.trim_end_matches('.').to_ascii_lowercase())
+            .collect::<Vec<_>>();
+        let host_ip_addresses = join_all(
+            names
+                .iter()
+                .map(|name| self.ip_addresses(name, Some(*ip_address))),
+        )
+        .await;
+
+        names
+            .into_iter()
+            .zip(host_ip_addresses)
+            // Errors of the address lookups are ignored (RFC 7208 section 5.5)
+            .filter(|(_, ip_addresses)| {
+                ip_addresses
+                    .as_ref()
+                    .is_ok_and(|ip_addresses| ip_addresses.contains(ip_address))
+            })
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// The validated host name for the `%{p}` macro, preferring the domain and its subdomains
+    async fn validated_domain(&self, ip_address: &IpAddr, domain_name: &str) -> Option<String> {
+        let validated_names = self.validated_names(ip_address).await;
+        let domain_name = domain_name.to_ascii_lowercase();
+        let subdomain_suffix = format!(".{}", domain_name);
+
+        validated_names
+            .iter()
+            .find(|name| **name == domain_name)
+            .or_else(|| {
+                validated_names
+                    .iter()
+                    .find(|name| name.ends_with(&subdomain_suffix))
+            })
+            .or(validated_names.first())
+            .cloned()
+    }
+
+    pub(crate) async fn resolve_exp_mut(
+        &self,
+        mut exp: ExpModifier,
+        query: &ResolveSpfQuery,
+    ) -> ExpModifier {
+        exp.domain_name = self.target_domain_name(Some(&exp.domain_spec), query).await;
+        exp.explain_string = match &exp.domain_name {
+            Some(domain_name) => self
+                .dns_resolver
+                .query_txt(&TxtRecordQuery {
+                    domain_name: domain_name.to_owned(),
+                })
+                .await
+                .ok()
+                .and_then(|txt_record| {
+                    // No explanation is used unless there is exactly one TXT record
+                    match txt_record.texts().as_slice() {
+                        [explain_string] => Some(explain_string.to_owned()),
+                        _ => None,
+                    }
+                }),
+            None => None,
+        };
+        exp.explanation = match (&exp.explain_string, &query.macro_context) {
+            (Some(explain_string), Some(_)) => self.explanation(explain_string, query).await,
+            _ => None,
+        };
+
+        exp
     }
 }
 
+/// The domain name as it is compared to detect loops
+pub(crate) fn chain_name(domain_name: &str) -> String {
+    domain_name.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// The term that was not resolved as no lookup was left in the budget
+fn lookup_limit_term(raw_rdata: &str, span: Range<usize>) -> Term {
+    Term::Unknown(UnknownTerm {
+        raw_rdata: raw_rdata[span.clone()].to_owned(),
+        span,
+        reason: Some(format!("Max lookup count of {} exceeded", MAX_LOOKUP_COUNT)),
+        lookup_error: None,
+        lookup_limit_exceeded: true,
+        error: None,
+    })
+}
+
+/// The term of an include or redirect whose target record could not be resolved
+fn unresolved_term(err: SpfError, raw_rdata: &str, span: Range<usize>) -> Term {
+    let reason = match err {
+        SpfError::IncludeLoop { chain } => {
+            return Term::Loop(LoopTerm {
+                raw_rdata: raw_rdata.to_owned(),
+                span,
+                chain,
+            })
+        }
+        SpfError::TempError(err) | SpfError::PermError(err) => {
+            return Term::Unknown(UnknownTerm {
+                raw_rdata: raw_rdata.to_owned(),
+                span,
+                reason: Some(err.to_string()),
+                lookup_error: Some(err),
+                lookup_limit_exceeded: false,
+                error: None,
+            })
+        }
+        SpfError::IncludeDepthExceeded { chain } => format!(
+            "Max include depth of {} exceeded: {}",
+            MAX_INCLUDE_DEPTH,
+            chain.join(" -> ")
+        ),
+        err => err.to_string(),
+    };
+
+    Term::new_unknown(raw_rdata, span, Some(reason))
+}
+
+/// The IP address of the sender, if the record is resolved for an evaluation
+fn sender_ip_address(query: &ResolveSpfQuery) -> Option<IpAddr> {
+    query
+        .macro_context
+        .as_ref()
+        .map(|context| context.ip_address.to_canonical())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use mockall::mock;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use std::str::FromStr;
 
-    use crate::dns::core::dns_resolver::{
-        ARecordQuery, DnsResolver, MxRecordQuery, TxtRecordQuery,
-    };
-    use crate::dns::domain::{ARecord, MxRecord, TxtRecord};
-    use crate::spf::domain::Term;
-    use std::error::Error;
+    use crate::dns::core::dns_resolver::MockDnsResolver;
+    use crate::dns::domain::{ARecord, AaaaRecord, MxRecord, PtrRecord, TxtRecord};
+    use crate::spf::domain::{CheckError, Term};
     use std::net::IpAddr;
 
-    mock! {
-        pub DnsResolver {}
-
-        impl DnsResolver for DnsResolver {
-            fn query_a(&mut self, query: &ARecordQuery) -> Result<ARecord, Box<dyn Error>>;
-            fn query_txt(&mut self, query: &TxtRecordQuery) -> Result<TxtRecord, Box<dyn Error>>;
-            fn query_mx(&mut self, query: &MxRecordQuery) -> Result<MxRecord, Box<dyn Error>>;
-        }
-    }
-
-    #[test]
-    fn it_should_return_no_spf_record_found() {
+    #[tokio::test]
+    async fn it_should_return_no_spf_record_found() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
             .expect_query_txt()
             .once()
-            .return_once(move |_| Ok(TxtRecord { records: vec![] }));
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
@@ -314,8 +757,67 @@ mod test {
         }
     }
 
-    #[test]
-    fn it_should_return_a_spf_record() {
+    #[tokio::test]
+    async fn it_should_return_no_spf_record_found_for_nxdomain() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |query| {
+                Err(DnsError::NxDomain {
+                    name: query.domain_name.to_owned(),
+                    negative_ttl: None,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        assert!(matches!(
+            *spf_summary.err().unwrap(),
+            SpfError::NoSpfRecordFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_return_temp_error_for_server_failure() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |query| Err(DnsError::ServFail(query.domain_name.to_owned())));
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        match *spf_summary.err().unwrap() {
+            SpfError::TempError(err) => assert_eq!(err.name(), "example.com"),
+            _ => panic!("Expected TempError error but was not returned"),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_return_multiple_spf_records_found() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -323,16 +825,97 @@ mod test {
             .once()
             .return_once(move |_| {
                 Ok(TxtRecord {
-                    records: vec!["v=spf1".to_owned()],
+                    records: vec![
+                        vec!["v=spf1 -all".to_owned()],
+                        vec!["google-site-verification=abc".to_owned()],
+                        vec!["V=SPF1 a -all".to_owned()],
+                    ],
+                    ttl: 3600,
                 })
             });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        match *spf_summary.err().unwrap() {
+            SpfError::MultipleSpfRecordsFound { records, .. } => assert_eq!(records.len(), 2),
+            _ => panic!("Expected MultipleSpfRecordsFound error but was not returned"),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_concatenate_character_strings_of_a_record() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![
+                        vec![
+                            "v=spf1 ip4:192.0.2.1 ip4:192.0".to_owned(),
+                            ".2.2 -all".to_owned(),
+                        ],
+                        vec!["v=spf10 -all".to_owned()],
+                    ],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert_eq!(
+            actual_spf_record.raw_rdata,
+            "v=spf1 ip4:192.0.2.1 ip4:192.0.2.2 -all"
+        );
+        assert_eq!(actual_spf_record.terms.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn it_should_return_a_spf_record() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
@@ -342,18 +925,21 @@ mod test {
         );
     }
 
-    #[test]
-    fn it_should_use_the_record_from_query() {
+    #[tokio::test]
+    async fn it_should_use_the_record_from_query() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver.expect_query_txt().never();
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: Some("v=spf1 -all".to_owned()),
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 -all".to_owned()),
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
@@ -363,8 +949,8 @@ mod test {
         );
     }
 
-    #[test]
-    fn it_should_be_a_valid_spf_version() {
+    #[tokio::test]
+    async fn it_should_be_a_valid_spf_version() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -372,16 +958,20 @@ mod test {
             .once()
             .return_once(move |_| {
                 Ok(TxtRecord {
-                    records: vec!["v=spf1 -all".to_owned()],
+                    records: vec![vec!["v=spf1 -all".to_owned()]],
+                    ttl: 3600,
                 })
             });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
@@ -391,8 +981,8 @@ mod test {
         assert_eq!(actual_version_str, expected_version_str);
     }
 
-    #[test]
-    fn it_should_be_a_valid_include_mechanism() {
+    #[tokio::test]
+    async fn it_should_be_a_valid_include_mechanism() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -401,35 +991,40 @@ mod test {
             .returning(move |query| {
                 if query.domain_name == "example.com" {
                     Ok(TxtRecord {
-                        records: vec!["v=spf1 include:_spf.example.com".to_owned()],
+                        records: vec![vec!["v=spf1 include:_spf.example.com".to_owned()]],
+                        ttl: 3600,
                     })
                 } else {
                     Ok(TxtRecord {
-                        records: vec!["v=spf1 -all".to_owned()],
+                        records: vec![vec!["v=spf1 -all".to_owned()]],
+                        ttl: 3600,
                     })
                 }
             });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
-        let actual_spf_answer = spf_summary.unwrap();
+        let actual_spf_record = spf_summary.unwrap();
 
-        assert_eq!(actual_spf_answer.terms.len(), 1);
+        assert_eq!(actual_spf_record.terms.len(), 1);
         assert!(matches!(
-            actual_spf_answer.terms.first().unwrap(),
+            actual_spf_record.terms.first().unwrap(),
             Term::Directive(e) if matches!(e.mechanism, Mechanism::Include(_))
         ));
     }
 
-    #[test]
-    fn it_should_be_a_valid_a_mechanism() {
+    #[tokio::test]
+    async fn it_should_be_a_valid_a_mechanism() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -437,35 +1032,167 @@ mod test {
             .once()
             .return_once(move |_| {
                 Ok(TxtRecord {
-                    records: vec!["v=spf1 a".to_owned()],
+                    records: vec![vec!["v=spf1 a".to_owned()]],
+                    ttl: 3600,
                 })
             });
         dns_resolver.expect_query_a().once().return_once(move |_| {
             Ok(ARecord {
-                ip_addresses: vec![IpAddr::from_str("127.0.0.1").unwrap()],
+                ip_addresses: vec![Ipv4Addr::from_str("127.0.0.1").unwrap()],
+                ttl: 3600,
             })
         });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
-
-        // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        dns_resolver
+            .expect_query_aaaa()
+            .once()
+            .return_once(move |_| {
+                Ok(AaaaRecord {
+                    ip_addresses: vec![Ipv6Addr::from_str("::1").unwrap()],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
-        let actual_spf_answer = spf_summary.unwrap();
+        let actual_spf_record = spf_summary.unwrap();
 
-        assert_eq!(actual_spf_answer.terms.len(), 1);
+        assert_eq!(actual_spf_record.terms.len(), 1);
         assert!(matches!(
-            actual_spf_answer.terms.first().unwrap(),
-            Term::Directive(e) if matches!(e.mechanism, Mechanism::A(_))
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(e) if matches!(&e.mechanism, Mechanism::A(a) if a.ip_addresses.len() == 2)
         ));
     }
 
-    #[test]
-    fn it_should_be_a_valid_mx_mechanism() {
+    #[tokio::test]
+    async fn it_should_keep_the_dns_error_of_a_failed_lookup() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_a()
+            .once()
+            .return_once(move |query| Err(DnsError::Timeout(query.domain_name.to_owned())));
+        dns_resolver
+            .expect_query_aaaa()
+            .once()
+            .return_once(move |query| {
+                Err(DnsError::NoData {
+                    name: query.domain_name.to_owned(),
+                    negative_ttl: None,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 a:mail.example.com -all".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Unknown(u) if u.lookup_error == Some(DnsError::Timeout("mail.example.com".to_owned()))
+                && u.raw_rdata == "a:mail.example.com"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_treat_nodata_as_void_lookup() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_mx()
+            .once()
+            .return_once(move |query| {
+                Err(DnsError::NoData {
+                    name: query.domain_name.to_owned(),
+                    negative_ttl: None,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 mx -all".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(d) if d.mechanism.is_void_lookup()
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_invalid_terms_without_panicking() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_a().once().return_once(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("127.0.0.1").unwrap()],
+                ttl: 3600,
+            })
+        });
+        dns_resolver
+            .expect_query_aaaa()
+            .once()
+            .return_once(move |_| {
+                Ok(AaaaRecord {
+                    ip_addresses: vec![Ipv6Addr::from_str("::1").unwrap()],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1  ip4:192.0.2.300  A//64 -all".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert_eq!(actual_spf_record.terms.len(), 3);
+        assert!(matches!(
+            &actual_spf_record.terms[0],
+            Term::Unknown(u) if u.error.is_some() && u.span == (8..23)
+        ));
+        assert!(matches!(
+            &actual_spf_record.terms[1],
+            Term::Directive(d) if matches!(&d.mechanism, Mechanism::A(a) if a.ip_addresses.len() == 2)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_be_a_valid_mx_mechanism() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -473,35 +1200,56 @@ mod test {
             .once()
             .return_once(move |_| {
                 Ok(TxtRecord {
-                    records: vec!["v=spf1 mx".to_owned()],
+                    records: vec![vec!["v=spf1 mx".to_owned()]],
+                    ttl: 3600,
                 })
             });
         dns_resolver.expect_query_mx().once().return_once(move |_| {
             Ok(MxRecord {
-                exchanges: vec!["example.com".to_owned()],
+                exchanges: vec!["mail.example.com".to_owned()],
+                ttl: 3600,
+            })
+        });
+        dns_resolver.expect_query_a().once().return_once(move |_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::from_str("192.0.2.1").unwrap()],
+                ttl: 3600,
             })
         });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        dns_resolver
+            .expect_query_aaaa()
+            .once()
+            .return_once(move |_| {
+                Ok(AaaaRecord {
+                    ip_addresses: vec![Ipv6Addr::from_str("::1").unwrap()],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
-        let actual_spf_answer = spf_summary.unwrap();
+        let actual_spf_record = spf_summary.unwrap();
 
-        assert_eq!(actual_spf_answer.terms.len(), 1);
+        assert_eq!(actual_spf_record.terms.len(), 1);
         assert!(matches!(
-            actual_spf_answer.terms.first().unwrap(),
-            Term::Directive(e) if matches!(e.mechanism, Mechanism::Mx(_))
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(e) if matches!(&e.mechanism, Mechanism::Mx(mx)
+                if mx.hosts[0].name == "mail.example.com" && mx.hosts[0].ip_addresses.len() == 2)
         ));
     }
 
-    #[test]
-    fn it_should_be_a_valid_ip4_mechanism() {
+    #[tokio::test]
+    async fn it_should_be_a_valid_ip4_mechanism() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -509,30 +1257,34 @@ mod test {
             .once()
             .return_once(move |_| {
                 Ok(TxtRecord {
-                    records: vec!["v=spf1 ip4:127.0.0.1".to_owned()],
+                    records: vec![vec!["v=spf1 ip4:127.0.0.1".to_owned()]],
+                    ttl: 3600,
                 })
             });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
-        let actual_spf_answer = spf_summary.unwrap();
+        let actual_spf_record = spf_summary.unwrap();
 
-        assert_eq!(actual_spf_answer.terms.len(), 1);
+        assert_eq!(actual_spf_record.terms.len(), 1);
         assert!(matches!(
-            actual_spf_answer.terms.first().unwrap(),
+            actual_spf_record.terms.first().unwrap(),
             Term::Directive(e) if matches!(e.mechanism, Mechanism::Ip4(_))
         ));
     }
 
-    #[test]
-    fn it_should_be_a_valid_ip6_mechanism() {
+    #[tokio::test]
+    async fn it_should_be_a_valid_ip6_mechanism() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -540,30 +1292,34 @@ mod test {
             .once()
             .return_once(move |_| {
                 Ok(TxtRecord {
-                    records: vec!["v=spf1 ip6:::1".to_owned()],
+                    records: vec![vec!["v=spf1 ip6:::1".to_owned()]],
+                    ttl: 3600,
                 })
             });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
-        let actual_spf_answer = spf_summary.unwrap();
+        let actual_spf_record = spf_summary.unwrap();
 
-        assert_eq!(actual_spf_answer.terms.len(), 1);
+        assert_eq!(actual_spf_record.terms.len(), 1);
         assert!(matches!(
-            actual_spf_answer.terms.first().unwrap(),
+            actual_spf_record.terms.first().unwrap(),
             Term::Directive(e) if matches!(e.mechanism, Mechanism::Ip6(_))
         ));
     }
 
-    #[test]
-    fn it_should_be_a_valid_all_mechanism() {
+    #[tokio::test]
+    async fn it_should_be_a_valid_all_mechanism() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -571,30 +1327,34 @@ mod test {
             .once()
             .return_once(move |_| {
                 Ok(TxtRecord {
-                    records: vec!["v=spf1 all".to_owned()],
+                    records: vec![vec!["v=spf1 all".to_owned()]],
+                    ttl: 3600,
                 })
             });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
-        let actual_spf_answer = spf_summary.unwrap();
+        let actual_spf_record = spf_summary.unwrap();
 
-        assert_eq!(actual_spf_answer.terms.len(), 1);
+        assert_eq!(actual_spf_record.terms.len(), 1);
         assert!(matches!(
-            actual_spf_answer.terms.first().unwrap(),
+            actual_spf_record.terms.first().unwrap(),
             Term::Directive(e) if matches!(e.mechanism, Mechanism::All(_))
         ));
     }
 
-    #[test]
-    fn it_should_be_a_valid_redirect_mechanism() {
+    #[tokio::test]
+    async fn it_should_be_a_valid_redirect_mechanism() {
         // Arrange
         let mut dns_resolver = MockDnsResolver::new();
         dns_resolver
@@ -603,30 +1363,566 @@ mod test {
             .returning(move |query| {
                 if query.domain_name == "example.com" {
                     Ok(TxtRecord {
-                        records: vec!["v=spf1 redirect=_spf.example.com".to_owned()],
+                        records: vec![vec!["v=spf1 redirect=_spf.example.com".to_owned()]],
+                        ttl: 3600,
                     })
                 } else {
                     Ok(TxtRecord {
-                        records: vec!["v=spf1 -all".to_owned()],
+                        records: vec![vec!["v=spf1 -all".to_owned()]],
+                        ttl: 3600,
                     })
                 }
             });
-        let mut spf_resolver = ResolveSpfUseCaseImpl::new(&mut dns_resolver);
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
 
         // Act
-        let spf_summary = spf_resolver.resolve(&ResolveSpfQuery {
-            domain_name: "example.com".to_owned(),
-            record: None,
-        });
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
 
         // Assert
         dns_resolver.checkpoint();
-        let actual_spf_answer = spf_summary.unwrap();
+        let actual_spf_record = spf_summary.unwrap();
 
-        assert_eq!(actual_spf_answer.terms.len(), 1);
+        assert_eq!(actual_spf_record.terms.len(), 1);
         assert!(matches!(
-            actual_spf_answer.terms.first().unwrap(),
+            actual_spf_record.terms.first().unwrap(),
             Term::Modifier(e) if matches!(e, Modifier::Redirect(_))
         ));
     }
+
+    #[tokio::test]
+    async fn it_should_detect_an_include_loop() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .times(2)
+            .returning(move |query| {
+                if query.domain_name == "example.com" {
+                    Ok(TxtRecord {
+                        records: vec![vec!["v=spf1 include:_spf.example.com -all".to_owned()]],
+                        ttl: 3600,
+                    })
+                } else {
+                    Ok(TxtRecord {
+                        records: vec![vec!["v=spf1 include:EXAMPLE.com".to_owned()]],
+                        ttl: 3600,
+                    })
+                }
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+        let Term::Directive(directive) = &actual_spf_record.terms[0] else {
+            panic!("Expected the include directive");
+        };
+        let Mechanism::Include(include) = &directive.mechanism else {
+            panic!("Expected the include mechanism");
+        };
+
+        assert!(matches!(
+            include.terms.first().unwrap(),
+            Term::Loop(l) if l.chain == ["example.com", "_spf.example.com", "example.com"]
+                && l.raw_rdata == "include:EXAMPLE.com"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_detect_a_redirect_to_itself() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1 redirect=example.com.".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Loop(l) if l.chain == ["example.com", "example.com"]
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_not_treat_repeated_includes_as_loop() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .times(2)
+            .returning(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1 -all".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 include:_spf.example.com include:_spf.example.com".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(actual_spf_record.terms.iter().all(|term| matches!(
+            term,
+            Term::Directive(d) if matches!(d.mechanism, Mechanism::Include(_))
+        )));
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_nested_includes_at_the_lookup_limit() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .times(MAX_LOOKUP_COUNT + 1)
+            .returning(move |query| {
+                Ok(TxtRecord {
+                    records: vec![vec![format!("v=spf1 include:x.{}", query.domain_name)]],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: None,
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let mut terms = &spf_summary.unwrap().terms;
+        while let Some(Term::Directive(Directive {
+            mechanism: Mechanism::Include(include),
+            ..
+        })) = terms.first()
+        {
+            terms = &include.terms;
+        }
+
+        assert!(matches!(
+            terms.first().unwrap(),
+            Term::Unknown(u) if u.reason.as_deref().is_some_and(|r| r.starts_with("Max lookup count"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_spend_the_lookup_limit_in_evaluation_order() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().once().returning(|_| {
+            let mechanisms = (1..10)
+                .map(|i| format!("a:mail{}.example.com", i))
+                .collect::<Vec<_>>();
+            Ok(TxtRecord {
+                records: vec![vec![format!("v=spf1 {} -all", mechanisms.join(" "))]],
+                ttl: 3600,
+            })
+        });
+        dns_resolver.expect_query_a().times(9).returning(|_| {
+            Ok(ARecord {
+                ip_addresses: vec![Ipv4Addr::new(192, 0, 2, 1)],
+                ttl: 3600,
+            })
+        });
+        dns_resolver.expect_query_aaaa().times(9).returning(|_| {
+            Ok(AaaaRecord {
+                ip_addresses: vec![Ipv6Addr::LOCALHOST],
+                ttl: 3600,
+            })
+        });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 include:_spf.example.com a:last.example.com -all".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            &actual_spf_record.terms[1],
+            Term::Unknown(u) if u.lookup_limit_exceeded && u.raw_rdata == "a:last.example.com"
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_share_the_lookup_limit_between_sibling_includes() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .times(MAX_LOOKUP_COUNT)
+            .returning(move |query| {
+                let includes = (0..10)
+                    .map(|i| format!("include:{}.{}", i, query.domain_name))
+                    .collect::<Vec<_>>();
+                Ok(TxtRecord {
+                    records: vec![vec![format!("v=spf1 {}", includes.join(" "))]],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+        let includes = (0..10)
+            .map(|i| format!("include:{}.example.com", i))
+            .collect::<Vec<_>>();
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some(format!("v=spf1 {}", includes.join(" "))),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        fn count_includes(terms: &[Term]) -> usize {
+            terms
+                .iter()
+                .map(|term| match term {
+                    Term::Directive(Directive {
+                        mechanism: Mechanism::Include(include),
+                        ..
+                    }) => 1 + count_includes(&include.terms),
+                    _ => 0,
+                })
+                .sum()
+        }
+
+        assert_eq!(count_includes(&actual_spf_record.terms), MAX_LOOKUP_COUNT);
+    }
+
+    #[tokio::test]
+    async fn it_should_expand_the_domain_macro_of_an_include() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .withf(|query| query.domain_name == "_spf.example.com")
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec!["v=spf1 -all".to_owned()]],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 include:_spf.%{d}".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(e) if matches!(&e.mechanism, Mechanism::Include(i) if i.terms.len() == 1)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_expand_sender_macros_with_context() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_a()
+            .once()
+            .withf(|query| query.domain_name == "3.2.0.192.in-addr._spf.example.com")
+            .return_once(move |_| {
+                Ok(ARecord {
+                    ip_addresses: vec![],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 a:%{ir}.%{v}._spf.%{d}".to_owned()),
+                macro_context: Some(MacroContext::new(
+                    "user@example.com",
+                    IpAddr::from_str("192.0.2.3").unwrap(),
+                    None,
+                )),
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        assert!(spf_summary.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_skip_lookup_of_sender_macros_without_context() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_txt().never();
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 include:%{i}._spf.%{d}".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(e) if matches!(&e.mechanism, Mechanism::Include(i) if i.terms.is_empty())
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_be_a_valid_exists_mechanism() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_a()
+            .once()
+            .withf(|query| query.domain_name == "192.0.2.3._spf.example.com")
+            .return_once(move |_| {
+                Ok(ARecord {
+                    ip_addresses: vec![Ipv4Addr::from_str("127.0.0.2").unwrap()],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 exists:%{i}._spf.%{d}".to_owned()),
+                macro_context: Some(MacroContext::new(
+                    "user@example.com",
+                    IpAddr::from_str("192.0.2.3").unwrap(),
+                    None,
+                )),
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert_eq!(actual_spf_record.terms.len(), 1);
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(e) if matches!(&e.mechanism, Mechanism::Exists(m) if m.ip_addresses.len() == 1)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_validate_the_host_names_of_a_ptr_mechanism() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_ptr()
+            .once()
+            .return_once(move |_| {
+                Ok(PtrRecord {
+                    names: vec![
+                        "mail.example.com.".to_owned(),
+                        "spoofed.example.com.".to_owned(),
+                    ],
+                    ttl: 3600,
+                })
+            });
+        dns_resolver
+            .expect_query_a()
+            .times(2)
+            .returning(move |query| {
+                let ip_address = if query.domain_name == "mail.example.com" {
+                    "192.0.2.3"
+                } else {
+                    "198.51.100.1"
+                };
+                Ok(ARecord {
+                    ip_addresses: vec![Ipv4Addr::from_str(ip_address).unwrap()],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 ptr".to_owned()),
+                macro_context: Some(MacroContext::new(
+                    "user@example.com",
+                    IpAddr::from_str("192.0.2.3").unwrap(),
+                    None,
+                )),
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(e) if matches!(&e.mechanism, Mechanism::Ptr(m)
+                if m.validated_names == Some(vec!["mail.example.com".to_owned()]))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_not_validate_ptr_mechanism_without_context() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver.expect_query_ptr().never();
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 ptr:example.org".to_owned()),
+                macro_context: None,
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert!(matches!(
+            actual_spf_record.terms.first().unwrap(),
+            Term::Directive(e) if matches!(&e.mechanism, Mechanism::Ptr(m) if m.validated_names.is_none())
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_be_a_valid_exp_modifier() {
+        // Arrange
+        let mut dns_resolver = MockDnsResolver::new();
+        dns_resolver
+            .expect_query_txt()
+            .once()
+            .withf(|query| query.domain_name == "explain.example.com")
+            .return_once(move |_| {
+                Ok(TxtRecord {
+                    records: vec![vec![
+                        "%{i} is not one of %{d}'s designated mail servers.".to_owned()
+                    ]],
+                    ttl: 3600,
+                })
+            });
+        let spf_resolver = ResolveSpfUseCaseImpl::new(&dns_resolver);
+
+        // Act
+        let spf_summary = spf_resolver
+            .resolve(&ResolveSpfQuery {
+                domain_name: "example.com".to_owned(),
+                record: Some("v=spf1 -all exp=explain.%{d}".to_owned()),
+                macro_context: Some(MacroContext::new(
+                    "user@example.com",
+                    IpAddr::from_str("192.0.2.3").unwrap(),
+                    None,
+                )),
+            })
+            .await;
+
+        // Assert
+        dns_resolver.checkpoint();
+        let actual_spf_record = spf_summary.unwrap();
+
+        assert_eq!(actual_spf_record.terms.len(), 2);
+        assert!(matches!(
+            &actual_spf_record.terms[1],
+            Term::Modifier(Modifier::Exp(e)) if e.explanation.as_deref()
+                == Some("192.0.2.3 is not one of example.com's designated mail servers.")
+        ));
+    }
+
+    #[test]
+    fn it_should_use_the_error_message_as_reason_of_an_unresolved_term() {
+        // Arrange
+        let err = SpfError::CheckFailed(CheckError {
+            summary: "Something went wrong".to_owned(),
+            description: "Details".to_owned(),
+        });
+
+        // Act
+        let term = unresolved_term(err, "include:example.org", 7..26);
+
+        // Assert
+        assert!(matches!(
+            term,
+            Term::Unknown(u) if u.reason.as_deref() == Some("Something went wrong")
+        ));
+    }
 }
@@ -1,12 +1,15 @@
 use crate::spf::domain::mechanism::Mechanism;
 use crate::spf::domain::qualifier::QualifierType;
 use std::fmt::{Display, Formatter};
+use std::ops::Range;
 
 pub struct Directive {
     /// The qualifier of the directive
     pub qualifier: Option<QualifierType>,
     /// The "value" of the directive (e.g. "example.com" for redirect)
     pub mechanism: Mechanism,
+    /// The byte range of the directive in the raw record
+    pub span: Range<usize>,
 }
 
 impl Display for Directive {
@@ -1,6 +1,27 @@
+use std::fmt::{Display, Formatter};
+
+use crate::common::syntax_error::{Severity, SyntaxError};
+use crate::dns::domain::DnsError;
+
 #[derive(Debug)]
 pub enum SpfError {
     NoSpfRecordFound(String),
+    MultipleSpfRecordsFound {
+        domain_name: String,
+        records: Vec<String>,
+    },
+    /// An include or redirect points back to a domain that is already being resolved
+    IncludeLoop {
+        chain: Vec<String>,
+    },
+    /// The includes and redirects are nested deeper than allowed
+    IncludeDepthExceeded {
+        chain: Vec<String>,
+    },
+    /// A DNS query failed, retrying later may succeed
+    TempError(DnsError),
+    /// A DNS query failed for a reason that will not resolve itself
+    PermError(DnsError),
     CheckFailed(CheckError),
     SyntaxError(SyntaxError),
 }
@@ -11,74 +32,52 @@ pub struct CheckError {
     pub description: String,
 }
 
-impl From<CheckError> for SpfError {
-    fn from(err: CheckError) -> Self {
-        Self::CheckFailed(err)
+impl SpfError {
+    /// Errors other than syntax errors always make the record invalid
+    pub fn severity(&self) -> Severity {
+        match self {
+            SpfError::SyntaxError(err) => err.severity.unwrap_or(Severity::Error),
+            _ => Severity::Error,
+        }
     }
 }
 
-pub type LabelSpan = miette::LabeledSpan;
-pub type Severity = miette::Severity;
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct SyntaxError {
-    pub message: String,
-    pub severity: Option<Severity>,
-    pub src: Option<String>,
-    pub src_labels: Option<Vec<LabelSpan>>,
-    pub help: Option<String>,
-    pub code: Option<String>,
-    pub code_url: Option<String>,
-}
-
-impl From<SyntaxError> for SpfError {
-    fn from(err: SyntaxError) -> Self {
-        Self::SyntaxError(err)
+impl Display for SpfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpfError::NoSpfRecordFound(message) => write!(f, "{}", message),
+            SpfError::MultipleSpfRecordsFound { domain_name, .. } => {
+                write!(f, "Multiple SPF records found for '{}'", domain_name)
+            }
+            SpfError::IncludeLoop { chain } => write!(f, "Include loop: {}", chain.join(" -> ")),
+            SpfError::IncludeDepthExceeded { chain } => {
+                write!(f, "Max include depth exceeded: {}", chain.join(" -> "))
+            }
+            SpfError::TempError(err) | SpfError::PermError(err) => write!(f, "{}", err),
+            SpfError::CheckFailed(err) => write!(f, "{}", err.summary),
+            SpfError::SyntaxError(err) => write!(f, "{}", err.message),
+        }
     }
 }
 
-impl SyntaxError {
-    pub fn new(message: impl Into<String>) -> Self {
-        Self {
-            message: message.into(),
-            severity: None,
-            src: None,
-            src_labels: None,
-            help: None,
-            code: None,
-            code_url: None,
+impl From<DnsError> for SpfError {
+    fn from(err: DnsError) -> Self {
+        if err.is_temporary() {
+            Self::TempError(err)
+        } else {
+            Self::PermError(err)
         }
     }
+}
 
-    pub fn with_src(mut self, code: impl Into<String>) -> Self {
-        self.src = Some(code.into());
-        self
-    }
-
-    pub fn with_src_labels(mut self, labels: impl IntoIterator<Item = LabelSpan>) -> Self {
-        self.src_labels = Some(labels.into_iter().collect());
-        self
-    }
-
-    pub fn with_severity(mut self, severity: Severity) -> Self {
-        self.severity = Some(severity);
-        self
-    }
-
-    pub fn with_help(mut self, help: impl Into<String>) -> Self {
-        self.help = Some(help.into());
-        self
+impl From<CheckError> for SpfError {
+    fn from(err: CheckError) -> Self {
+        Self::CheckFailed(err)
     }
+}
 
-    pub fn with_code(mut self, code: impl Into<String>, url: Option<impl Into<String>>) -> Self {
-        self.code = Some(code.into());
-        if let Some(url) = url {
-            self.code_url = Some(url.into());
-        }
-        self
-    }
-    pub fn with_code_url(mut self, url: impl Into<String>) -> Self {
-        self.code_url = Some(url.into());
-        self
+impl From<SyntaxError> for SpfError {
+    fn from(err: SyntaxError) -> Self {
+        Self::SyntaxError(err)
     }
 }
@@ -0,0 +1,596 @@
+use std::net::IpAddr;
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::spf::domain::{LabelSpan, SyntaxError};
+
+/// Maximum length of a domain name after macro expansion (RFC 7208 section 7.3)
+const MAX_DOMAIN_NAME_LENGTH: usize = 253;
+
+/// Delimiters that may be used to split a macro value (RFC 7208 section 7.1)
+const DELIMITERS: [char; 7] = ['.', '-', '+', ',', '/', '_', '='];
+
+/// A string that may contain macros (RFC 7208 section 7)
+///
+/// e.g. `%{ir}.%{v}._spf.%{d}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroString {
+    pub items: Vec<MacroItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroItem {
+    /// Literal text including the expanded escapes `%%`, `%_` and `%-`
+    Literal(String),
+    /// A macro expansion (e.g. `%{d2r}`)
+    Expand(MacroExpand),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroExpand {
+    /// The macro letter (e.g. `d` for the current domain)
+    pub letter: MacroLetter,
+
+    /// The number of right-hand parts to use after optional reversal
+    pub digits: Option<usize>,
+
+    /// Whether the parts are reversed before the digits are applied
+    pub reverse: bool,
+
+    /// The characters the value is split on (defaults to '.')
+    pub delimiters: Vec<char>,
+
+    /// Uppercase macro letters are URL escaped
+    pub url_escape: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroLetter {
+    /// `s`: the sender
+    Sender,
+    /// `l`: the local-part of the sender
+    LocalPart,
+    /// `o`: the domain of the sender
+    SenderDomain,
+    /// `d`: the current domain
+    Domain,
+    /// `i`: the IP address in dotted format
+    Ip,
+    /// `p`: the validated domain name of the IP address
+    ValidatedDomain,
+    /// `v`: the string "in-addr" for IPv4 or "ip6" for IPv6
+    IpVersion,
+    /// `h`: the HELO/EHLO domain
+    Helo,
+    /// `c`: the readable IP address (explanation only)
+    ReadableIp,
+    /// `r`: the domain name of the receiving host (explanation only)
+    Receiver,
+    /// `t`: the current timestamp (explanation only)
+    Timestamp,
+}
+
+impl MacroLetter {
+    fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_lowercase() {
+            's' => Some(MacroLetter::Sender),
+            'l' => Some(MacroLetter::LocalPart),
+            'o' => Some(MacroLetter::SenderDomain),
+            'd' => Some(MacroLetter::Domain),
+            'i' => Some(MacroLetter::Ip),
+            'p' => Some(MacroLetter::ValidatedDomain),
+            'v' => Some(MacroLetter::IpVersion),
+            'h' => Some(MacroLetter::Helo),
+            'c' => Some(MacroLetter::ReadableIp),
+            'r' => Some(MacroLetter::Receiver),
+            't' => Some(MacroLetter::Timestamp),
+            _ => None,
+        }
+    }
+
+    /// The letters `c`, `r` and `t` are only allowed in explanation strings
+    fn is_explanation_only(&self) -> bool {
+        matches!(
+            self,
+            MacroLetter::ReadableIp | MacroLetter::Receiver | MacroLetter::Timestamp
+        )
+    }
+}
+
+/// The values available for macro expansion during a `check_host()` evaluation
+#[derive(Debug, Clone)]
+pub struct MacroContext {
+    /// The "MAIL FROM" identity (e.g. "user@example.com")
+    pub sender: String,
+
+    /// The IP address of the SMTP client
+    pub ip_address: IpAddr,
+
+    /// The domain name given in the SMTP HELO or EHLO command
+    pub helo: Option<String>,
+
+    /// The validated domain name of the IP address
+    pub validated_domain: Option<String>,
+
+    /// The domain name of the receiving host
+    pub receiver: Option<String>,
+
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl MacroContext {
+    pub fn new(sender: impl Into<String>, ip_address: IpAddr, helo: Option<String>) -> Self {
+        MacroContext {
+            sender: sender.into(),
+            ip_address,
+            helo,
+            validated_domain: None,
+            receiver: None,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl MacroString {
+    /// Parse a domain-spec (e.g. the value of `include:`)
+    pub fn parse(value: &str) -> Result<Self, Box<SyntaxError>> {
+        Self::parse_with(value, false)
+    }
+
+    /// Parse an explanation string that is allowed to contain the letters `c`, `r` and `t`
+    pub fn parse_explain_string(value: &str) -> Result<Self, Box<SyntaxError>> {
+        Self::parse_with(value, true)
+    }
+
+    /// Returns true if the string contains at least one macro expansion
+    pub fn has_macros(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(item, MacroItem::Expand(_)))
+    }
+
+    /// Returns true if the string uses the given macro letter
+    pub fn has_letter(&self, letter: MacroLetter) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(item, MacroItem::Expand(expand) if expand.letter == letter))
+    }
+
+    /// Expand the macro string for the current domain.
+    ///
+    /// Returns `None` if a macro depends on the sender but no context is given.
+    pub fn expand(&self, domain: &str, context: Option<&MacroContext>) -> Option<String> {
+        self.items
+            .iter()
+            .map(|item| match item {
+                MacroItem::Literal(literal) => Some(literal.to_owned()),
+                MacroItem::Expand(expand) => expand.expand(domain, context),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| parts.concat())
+    }
+
+    /// Expand the macro string and truncate it to a valid domain name length
+    pub fn expand_domain_spec(
+        &self,
+        domain: &str,
+        context: Option<&MacroContext>,
+    ) -> Option<String> {
+        let mut domain_name = self.expand(domain, context)?;
+
+        // Labels are removed from the left until the total length does not exceed 253 characters
+        while domain_name.len() > MAX_DOMAIN_NAME_LENGTH {
+            match domain_name.split_once('.') {
+                Some((_, rest)) => domain_name = rest.to_owned(),
+                None => break,
+            }
+        }
+
+        Some(domain_name)
+    }
+
+    fn parse_with(value: &str, is_explanation: bool) -> Result<Self, Box<SyntaxError>> {
+        let mut items = vec![];
+        let mut literal = String::new();
+        let mut index = 0;
+
+        while let Some(c) = value[index..].chars().next() {
+            let start = index;
+            index += c.len_utf8();
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+
+            match value[index..].chars().next() {
+                Some('%') => literal.push('%'),
+                Some('_') => literal.push(' '),
+                Some('-') => literal.push_str("%20"),
+                Some('{') => {
+                    let Some(length) = value[start..].find('}') else {
+                        return Err(macro_error(
+                            value,
+                            start..value.len(),
+                            "Macro is not terminated",
+                            "Close the macro with '}'.",
+                        ));
+                    };
+                    let end = start + length;
+                    let expand = MacroExpand::parse(value, start + 2..end, is_explanation)?;
+
+                    if !literal.is_empty() {
+                        items.push(MacroItem::Literal(std::mem::take(&mut literal)));
+                    }
+                    items.push(MacroItem::Expand(expand));
+
+                    // continue after the closing brace
+                    index = end + 1;
+                    continue;
+                }
+                Some(c) => {
+                    return Err(macro_error(
+                        value,
+                        start..index + c.len_utf8(),
+                        format!("'%{}' is not a valid macro", c),
+                        "Use '%%' to write a literal '%'.",
+                    ))
+                }
+                None => {
+                    return Err(macro_error(
+                        value,
+                        start..value.len(),
+                        "Incomplete macro",
+                        "Use '%%' to write a literal '%'.",
+                    ))
+                }
+            }
+            index += 1;
+        }
+
+        if !literal.is_empty() {
+            items.push(MacroItem::Literal(literal));
+        }
+
+        Ok(MacroString { items })
+    }
+}
+
+impl MacroExpand {
+    fn parse(
+        value: &str,
+        span: Range<usize>,
+        is_explanation: bool,
+    ) -> Result<Self, Box<SyntaxError>> {
+        let body = &value[span.clone()];
+        let mut chars = body.char_indices().peekable();
+
+        let Some((_, letter_char)) = chars.next() else {
+            return Err(macro_error(
+                value,
+                span.start - 2..span.end + 1,
+                "Macro letter is missing",
+                "Use one of the macro letters s, l, o, d, i, p, h or v.",
+            ));
+        };
+        let letter_span = span.start..span.start + letter_char.len_utf8();
+        let Some(letter) = MacroLetter::from_char(letter_char) else {
+            return Err(macro_error(
+                value,
+                letter_span,
+                format!("'{}' is not a valid macro letter", letter_char),
+                "Use one of the macro letters s, l, o, d, i, p, h or v.",
+            ));
+        };
+        if letter.is_explanation_only() && !is_explanation {
+            return Err(macro_error(
+                value,
+                letter_span,
+                format!("'{}' is only allowed in explanation strings", letter_char),
+                "Use one of the macro letters s, l, o, d, i, p, h or v.",
+            ));
+        }
+
+        let mut digits = String::new();
+        while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+            digits.push(c);
+        }
+        let digits = if digits.is_empty() {
+            None
+        } else {
+            let digits_span = letter_span.end..letter_span.end + digits.len();
+            match digits.parse::<usize>() {
+                Ok(0) => {
+                    return Err(macro_error(
+                        value,
+                        digits_span,
+                        "Number of parts must not be zero",
+                        "Remove the digits to use all parts.",
+                    ))
+                }
+                Ok(digits) => Some(digits),
+                Err(_) => {
+                    return Err(macro_error(
+                        value,
+                        digits_span,
+                        "Number of parts is too large",
+                        "Remove the digits to use all parts.",
+                    ))
+                }
+            }
+        };
+
+        let reverse = chars.next_if(|(_, c)| matches!(c, 'r' | 'R')).is_some();
+
+        let mut delimiters = vec![];
+        for (index, c) in chars {
+            if !DELIMITERS.contains(&c) {
+                let start = span.start + index;
+                return Err(macro_error(
+                    value,
+                    start..start + c.len_utf8(),
+                    format!("'{}' is not a valid delimiter", c),
+                    "Use one of the delimiters '.', '-', '+', ',', '/', '_' or '='.",
+                ));
+            }
+            delimiters.push(c);
+        }
+
+        Ok(MacroExpand {
+            letter,
+            digits,
+            reverse,
+            delimiters,
+            url_escape: letter_char.is_ascii_uppercase(),
+        })
+    }
+
+    fn expand(&self, domain: &str, context: Option<&MacroContext>) -> Option<String> {
+        let value = match self.letter {
+            MacroLetter::Domain => domain.to_owned(),
+            letter => {
+                let context = context?;
+                match letter {
+                    MacroLetter::Sender => context.sender.to_owned(),
+                    MacroLetter::LocalPart => match context.sender.rsplit_once('@') {
+                        Some(("", _)) | None => "postmaster".to_owned(),
+                        Some((local_part, _)) => local_part.to_owned(),
+                    },
+                    MacroLetter::SenderDomain => match context.sender.rsplit_once('@') {
+                        Some((_, sender_domain)) => sender_domain.to_owned(),
+                        None => context.sender.to_owned(),
+                    },
+                    MacroLetter::Ip => dotted_ip_address(&context.ip_address),
+                    MacroLetter::ValidatedDomain => context
+                        .validated_domain
+                        .to_owned()
+                        .unwrap_or_else(|| "unknown".to_owned()),
+                    MacroLetter::IpVersion => match context.ip_address {
+                        IpAddr::V4(_) => "in-addr".to_owned(),
+                        IpAddr::V6(_) => "ip6".to_owned(),
+                    },
+                    MacroLetter::Helo => context
+                        .helo
+                        .to_owned()
+                        .unwrap_or_else(|| "unknown".to_owned()),
+                    MacroLetter::ReadableIp => context.ip_address.to_string(),
+                    MacroLetter::Receiver => context
+                        .receiver
+                        .to_owned()
+                        .unwrap_or_else(|| "unknown".to_owned()),
+                    MacroLetter::Timestamp => context.timestamp.to_string(),
+                    MacroLetter::Domain => unreachable!(),
+                }
+            }
+        };
+
+        let delimiters = if self.delimiters.is_empty() {
+            &['.'][..]
+        } else {
+            &self.delimiters[..]
+        };
+        let mut parts = value.split(delimiters).collect::<Vec<_>>();
+        if self.reverse {
+            parts.reverse();
+        }
+        if let Some(digits) = self.digits {
+            parts = parts.split_off(parts.len().saturating_sub(digits));
+        }
+
+        let expanded = parts.join(".");
+        if self.url_escape {
+            Some(url_escape(&expanded))
+        } else {
+            Some(expanded)
+        }
+    }
+}
+
+/// IPv4 addresses are dotted quads, IPv6 addresses are dot-separated nibbles
+fn dotted_ip_address(ip_address: &IpAddr) -> String {
+    match ip_address {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => ip
+            .octets()
+            .iter()
+            .flat_map(|octet| [octet >> 4, octet & 0x0f])
+            .map(|nibble| format!("{:x}", nibble))
+            .collect::<Vec<_>>()
+            .join("."),
+    }
+}
+
+/// Escape all characters except the unreserved characters of RFC 3986
+fn url_escape(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn macro_error(
+    src: &str,
+    span: Range<usize>,
+    message: impl Into<String>,
+    help: impl Into<String>,
+) -> Box<SyntaxError> {
+    let message = message.into();
+    Box::new(
+        SyntaxError::new(format!("Invalid macro string: {}", message))
+            .with_src(src)
+            .with_src_labels(vec![LabelSpan::at(span, message)])
+            .with_help(help),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::str::FromStr;
+
+    fn context(ip_address: &str) -> MacroContext {
+        MacroContext::new(
+            "strong-bad@email.example.com",
+            IpAddr::from_str(ip_address).unwrap(),
+            None,
+        )
+    }
+
+    fn expand(value: &str, ip_address: &str) -> String {
+        MacroString::parse(value)
+            .unwrap()
+            .expand("email.example.com", Some(&context(ip_address)))
+            .unwrap()
+    }
+
+    #[test]
+    fn it_should_expand_the_rfc_examples() {
+        let examples = [
+            ("%{s}", "strong-bad@email.example.com"),
+            ("%{o}", "email.example.com"),
+            ("%{d}", "email.example.com"),
+            ("%{d4}", "email.example.com"),
+            ("%{d3}", "email.example.com"),
+            ("%{d2}", "example.com"),
+            ("%{d1}", "com"),
+            ("%{dr}", "com.example.email"),
+            ("%{d2r}", "example.email"),
+            ("%{l}", "strong-bad"),
+            ("%{l-}", "strong.bad"),
+            ("%{lr}", "strong-bad"),
+            ("%{lr-}", "bad.strong"),
+            ("%{l1r-}", "strong"),
+            (
+                "%{ir}.%{v}._spf.%{d2}",
+                "3.2.0.192.in-addr._spf.example.com",
+            ),
+            ("%{lr-}.lp._spf.%{d2}", "bad.strong.lp._spf.example.com"),
+            (
+                "%{lr-}.lp.%{ir}.%{v}._spf.%{d2}",
+                "bad.strong.lp.3.2.0.192.in-addr._spf.example.com",
+            ),
+            (
+                "%{ir}.%{v}.%{l1r-}.lp._spf.%{d2}",
+                "3.2.0.192.in-addr.strong.lp._spf.example.com",
+            ),
+            (
+                "%{d2}.trusted-domains.example.net",
+                "example.com.trusted-domains.example.net",
+            ),
+        ];
+
+        for (value, expected) in examples {
+            assert_eq!(expand(value, "192.0.2.3"), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn it_should_expand_ipv6_address_as_nibbles() {
+        assert_eq!(
+            expand("%{ir}.%{v}._spf.%{d2}", "2001:db8::cb01"),
+            "1.0.b.c.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6._spf.example.com"
+        );
+    }
+
+    #[test]
+    fn it_should_expand_escapes() {
+        assert_eq!(expand("a%%b%_c%-d", "192.0.2.3"), "a%b c%20d");
+    }
+
+    #[test]
+    fn it_should_url_escape_uppercase_letters() {
+        let macro_string = MacroString::parse_explain_string("%{S}").unwrap();
+        let mut context = context("192.0.2.3");
+        context.sender = "user+tag@example.com".to_owned();
+
+        let expanded = macro_string.expand("example.com", Some(&context));
+
+        assert_eq!(expanded.unwrap(), "user%2Btag%40example.com");
+    }
+
+    #[test]
+    fn it_should_expand_only_domain_without_context() {
+        let domain_only = MacroString::parse("_spf.%{d}").unwrap();
+        let with_sender = MacroString::parse("%{i}._spf.%{d}").unwrap();
+
+        assert_eq!(
+            domain_only.expand("example.com", None).unwrap(),
+            "_spf.example.com"
+        );
+        assert!(with_sender.expand("example.com", None).is_none());
+    }
+
+    #[test]
+    fn it_should_truncate_long_domain_names_from_the_left() {
+        let label = "a".repeat(63);
+        let value = format!("{0}.{0}.{0}.{0}.%{{d}}", label);
+        let macro_string = MacroString::parse(&value).unwrap();
+
+        let expanded = macro_string
+            .expand_domain_spec("example.com", None)
+            .unwrap();
+
+        assert!(expanded.len() <= MAX_DOMAIN_NAME_LENGTH);
+        assert!(expanded.ends_with(".example.com"));
+    }
+
+    #[test]
+    fn it_should_report_invalid_macro_letter_with_span() {
+        let err = MacroString::parse("_spf.%{x}.example.com").unwrap_err();
+
+        let label = err.src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 7);
+        assert_eq!(label.len(), 1);
+    }
+
+    #[test]
+    fn it_should_report_invalid_delimiter_after_previous_macro() {
+        let err = MacroString::parse("%{d}.%{l1r;}").unwrap_err();
+
+        let label = err.src_labels.unwrap().pop().unwrap();
+        assert_eq!(label.offset(), 10);
+        assert_eq!(err.src.unwrap(), "%{d}.%{l1r;}");
+    }
+
+    #[test]
+    fn it_should_reject_explanation_letters_in_domain_spec() {
+        assert!(MacroString::parse("%{t}.example.com").is_err());
+        assert!(MacroString::parse_explain_string("%{t}.example.com").is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_invalid_macros() {
+        for value in ["%{d0}", "%{d", "%{}", "50%", "%x"] {
+            assert!(MacroString::parse(value).is_err(), "{}", value);
+        }
+    }
+}
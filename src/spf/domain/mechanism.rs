@@ -26,6 +26,28 @@ impl Mechanism {
                 | Mechanism::Exists(_)
         )
     }
+
+    /// Returns true if the DNS lookup of the mechanism returned no answer (RFC 7208 section 4.6.4)
+    pub fn is_void_lookup(&self) -> bool {
+        match self {
+            Mechanism::A(m) => m.domain_name.is_some() && m.ip_addresses.is_empty(),
+            Mechanism::Mx(m) => m.domain_name.is_some() && m.hosts.is_empty(),
+            Mechanism::Exists(m) => m.domain_name.is_some() && m.ip_addresses.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// The domain-spec of the mechanism, if one was given
+    pub fn domain_spec(&self) -> Option<&str> {
+        match self {
+            Mechanism::A(m) => m.domain_spec.as_deref(),
+            Mechanism::Mx(m) => m.domain_spec.as_deref(),
+            Mechanism::Include(m) => Some(&m.domain_spec),
+            Mechanism::Exists(m) => Some(&m.domain_spec),
+            Mechanism::Ptr(m) => m.domain_spec.as_deref(),
+            _ => None,
+        }
+    }
 }
 
 impl FromStr for Mechanism {
@@ -60,10 +82,19 @@ pub struct AMechanism {
     /// The raw value of the mechanism
     pub raw_value: String,
 
+    /// The domain name to query (defaults to the current domain)
+    pub domain_spec: Option<String>,
+
+    /// The expanded domain name, if it does not depend on the sender
+    pub domain_name: Option<String>,
+
     pub ip_addresses: Vec<IpAddr>,
 
-    /// Subnet mask
-    pub subnet_mask: Option<u8>,
+    /// Prefix length applied to IPv4 addresses
+    pub ip4_subnet_mask: Option<u8>,
+
+    /// Prefix length applied to IPv6 addresses
+    pub ip6_subnet_mask: Option<u8>,
 }
 
 pub struct IncludeMechanism {
@@ -86,15 +117,71 @@ pub struct MxMechanism {
     /// The raw value of the mechanism
     pub raw_value: String,
 
-    pub hosts: Vec<String>,
+    /// The domain name to query (defaults to the current domain)
+    pub domain_spec: Option<String>,
 
-    /// Subnet mask
-    pub subnet_mask: Option<u8>,
+    /// The expanded domain name, if it does not depend on the sender
+    pub domain_name: Option<String>,
+
+    /// All exchanges of the MX answer, only the first hosts are resolved to IP addresses
+    pub hosts: Vec<MxHost>,
+
+    /// Prefix length applied to IPv4 addresses of the hosts
+    pub ip4_subnet_mask: Option<u8>,
+
+    /// Prefix length applied to IPv6 addresses of the hosts
+    pub ip6_subnet_mask: Option<u8>,
+}
+impl MxMechanism {
+    /// Maximum number of MX names per mechanism (RFC 7208 section 4.6.4)
+    pub const MAX_HOSTS: usize = 10;
+
+    /// Returns true if the MX answer has more names than allowed, which results in a permerror
+    pub fn exceeds_host_limit(&self) -> bool {
+        self.hosts.len() > Self::MAX_HOSTS
+    }
+}
+pub struct MxHost {
+    /// The host name of the mail exchange
+    pub name: String,
+
+    /// The IP addresses of the host
+    pub ip_addresses: Vec<IpAddr>,
 }
 pub struct PtrMechanism {
     /// The raw value of the mechanism
     pub raw_value: String,
+
+    /// The target domain name (defaults to the current domain)
+    pub domain_spec: Option<String>,
+
+    /// The expanded target domain name, if it does not depend on the sender
+    pub domain_name: Option<String>,
+
+    /// Validated host names of the sender IP address (only known during an evaluation)
+    pub validated_names: Option<Vec<String>>,
 }
+impl PtrMechanism {
+    /// Maximum number of PTR names that are validated, the rest is ignored (RFC 7208 section 4.6.4)
+    pub const MAX_NAMES: usize = 10;
+
+    /// Returns true if a validated host name is the target domain or one of its subdomains
+    pub fn matches(&self) -> bool {
+        let (Some(domain_name), Some(validated_names)) = (&self.domain_name, &self.validated_names)
+        else {
+            return false;
+        };
+
+        let domain_name = domain_name.trim_end_matches('.').to_ascii_lowercase();
+        validated_names.iter().any(|name| {
+            name == &domain_name
+                || name
+                    .strip_suffix(&domain_name)
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+}
+
 pub struct Ip4Mechanism {
     /// The raw value of the mechanism
     pub raw_value: String,
@@ -118,4 +205,13 @@ pub struct Ip6Mechanism {
 pub struct ExistsMechanism {
     /// The raw value of the mechanism
     pub raw_value: String,
+
+    /// The domain-spec which may contain macros (e.g. "%{i}._spf.example.com")
+    pub domain_spec: String,
+
+    /// The expanded domain name, if it does not depend on the sender
+    pub domain_name: Option<String>,
+
+    /// IPv4 addresses of the expanded domain name
+    pub ip_addresses: Vec<IpAddr>,
 }
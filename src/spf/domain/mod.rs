@@ -1,17 +1,26 @@
 mod directive;
 mod error;
+mod macro_string;
 mod mechanism;
 mod modifier;
+mod parser;
 mod qualifier;
+mod record;
+mod result;
 mod term;
 mod version;
 
+pub use crate::common::syntax_error::{LabelSpan, Severity, SyntaxError};
 pub use directive::Directive;
-pub use error::{CheckError, LabelSpan, Severity, SpfError, SyntaxError};
+pub use error::{CheckError, SpfError};
+pub use macro_string::{MacroContext, MacroExpand, MacroItem, MacroLetter, MacroString};
 pub use mechanism::{
-    AMechanism, AllMechanism, IncludeMechanism, Ip4Mechanism, Ip6Mechanism, Mechanism, MxMechanism,
+    AMechanism, AllMechanism, ExistsMechanism, IncludeMechanism, Ip4Mechanism, Ip6Mechanism,
+    Mechanism, MxHost, MxMechanism, PtrMechanism,
 };
-pub use modifier::{Modifier, RedirectModifier};
+pub use modifier::{ExpModifier, Modifier, RedirectModifier};
 pub use qualifier::QualifierType;
-pub use term::{Term, UnknownTerm};
+pub use record::SpfRecord;
+pub use result::SpfResult;
+pub use term::{LoopTerm, Term, UnknownTerm};
 pub use version::Version;
@@ -1,5 +1,6 @@
 use crate::spf::domain::{Term, Version};
 use std::fmt::{Display, Formatter};
+use std::ops::Range;
 
 pub enum Modifier {
     Exp(ExpModifier),
@@ -10,6 +11,22 @@ impl Modifier {
     pub(crate) fn need_lookup(&self) -> bool {
         matches!(self, Modifier::Redirect(_))
     }
+
+    /// The byte range of the modifier in the raw record
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Modifier::Exp(e) => e.span.clone(),
+            Modifier::Redirect(r) => r.span.clone(),
+        }
+    }
+
+    /// The domain-spec of the modifier
+    pub fn domain_spec(&self) -> Option<&str> {
+        match self {
+            Modifier::Redirect(r) => Some(&r.domain_spec),
+            Modifier::Exp(e) => Some(&e.domain_spec),
+        }
+    }
 }
 
 impl Display for Modifier {
@@ -22,13 +39,32 @@ impl Display for Modifier {
 }
 
 pub struct ExpModifier {
+    /// The raw value of the modifier
     pub raw_value: String,
+
+    /// The byte range of the modifier in the raw record
+    pub span: Range<usize>,
+
+    /// The domain name of the explanation TXT record
+    pub domain_spec: String,
+
+    /// The expanded domain name, if it does not depend on the sender
+    pub domain_name: Option<String>,
+
+    /// The explain-string of the TXT record, if exactly one record was found
+    pub explain_string: Option<String>,
+
+    /// The explain-string with expanded macros (only known during an evaluation)
+    pub explanation: Option<String>,
 }
 
 pub struct RedirectModifier {
     /// The raw value of the mechanism
     pub raw_value: String,
 
+    /// The byte range of the modifier in the raw record
+    pub span: Range<usize>,
+
     /// The version of the SPF record (e.g. "spf1")
     pub version: Version,
 
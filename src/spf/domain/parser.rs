@@ -0,0 +1,736 @@
+//! Parser for the SPF record grammar (RFC 7208 section 12)
+//!
+//! The parser never fails: terms that do not follow the grammar are returned as
+//! [`Term::Unknown`] with a [`SyntaxError`] pointing at the offending bytes.
+
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::spf::domain::{
+    AMechanism, AllMechanism, Directive, ExistsMechanism, ExpModifier, IncludeMechanism,
+    Ip4Mechanism, Ip6Mechanism, LabelSpan, MacroString, Mechanism, Modifier, MxMechanism,
+    PtrMechanism, QualifierType, RedirectModifier, SyntaxError, Term, UnknownTerm, Version,
+};
+
+/// Parse the version and the terms of a raw SPF record
+pub fn parse_record(raw_rdata: &str) -> (Version, Vec<Term>) {
+    let mut spans = term_spans(raw_rdata).into_iter();
+
+    let version = spans
+        .next()
+        .map(|span| &raw_rdata[span])
+        .unwrap_or_default();
+    let terms = spans
+        .map(|span| parse_term(raw_rdata, span))
+        .collect::<Vec<_>>();
+
+    (Version::from_str(version).unwrap(), terms)
+}
+
+/// Parse the term at the given span of a raw SPF record
+pub fn parse_term(raw_rdata: &str, span: Range<usize>) -> Term {
+    let parser = Parser { raw_rdata };
+
+    parser.term(span.clone()).unwrap_or_else(|err| {
+        Term::Unknown(UnknownTerm {
+            raw_rdata: raw_rdata[span.clone()].to_string(),
+            span,
+            reason: None,
+            lookup_error: None,
+            lookup_limit_exceeded: false,
+            error: Some(*err),
+        })
+    })
+}
+
+/// Terms are separated by one or more spaces
+fn term_spans(raw_rdata: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut start = None;
+
+    for (index, c) in raw_rdata.char_indices() {
+        match (c, start) {
+            (' ', Some(begin)) => {
+                spans.push(begin..index);
+                start = None;
+            }
+            (' ', None) => {}
+            (_, None) => start = Some(index),
+            (_, Some(_)) => {}
+        }
+    }
+    if let Some(begin) = start {
+        spans.push(begin..raw_rdata.len());
+    }
+
+    spans
+}
+
+struct Parser<'a> {
+    raw_rdata: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn term(&self, span: Range<usize>) -> Result<Term, Box<SyntaxError>> {
+        let token = &self.raw_rdata[span.clone()];
+
+        let qualifier = match token.chars().next() {
+            Some(c @ ('+' | '-' | '~' | '?')) => QualifierType::from_str(&c.to_string()).ok(),
+            _ => None,
+        };
+        let body_start = span.start + usize::from(qualifier.is_some());
+        let body = &self.raw_rdata[body_start..span.end];
+
+        let name_length = body.find([':', '/', '=']).unwrap_or(body.len());
+        let name = &body[..name_length];
+        let name_span = body_start..body_start + name_length;
+        let rest = &body[name_length..];
+        let rest_start = name_span.end;
+
+        if let Some(value) = rest.strip_prefix('=') {
+            if qualifier.is_some() {
+                return Err(self.error(
+                    span.start..body_start,
+                    "Modifier with qualifier",
+                    "Modifiers can not have a qualifier",
+                    format!("Remove the qualifier of '{}'.", name),
+                ));
+            }
+            return self.modifier(span, name, name_span, value, rest_start + 1);
+        }
+
+        let raw_value = body.to_string();
+        let mechanism = match name.to_ascii_lowercase().as_str() {
+            "all" => {
+                if !rest.is_empty() {
+                    return Err(self.error(
+                        rest_start..span.end,
+                        "Invalid 'all' mechanism",
+                        "'all' does not take arguments",
+                        "Remove the arguments of 'all'.",
+                    ));
+                }
+                Mechanism::All(AllMechanism { raw_value })
+            }
+            "include" => Mechanism::Include(IncludeMechanism {
+                raw_value,
+                version: Version::from_str("").unwrap(),
+                domain_spec: self.required_domain_spec(name, rest, rest_start)?,
+                terms: vec![],
+                raw_rdata: String::new(),
+            }),
+            "a" => {
+                let (domain_spec, cidr_start) = self.optional_domain_spec(rest, rest_start)?;
+                let (ip4_subnet_mask, ip6_subnet_mask) =
+                    self.dual_cidr_length(cidr_start, span.end)?;
+                Mechanism::A(AMechanism {
+                    raw_value,
+                    domain_spec,
+                    domain_name: None,
+                    ip_addresses: vec![],
+                    ip4_subnet_mask,
+                    ip6_subnet_mask,
+                })
+            }
+            "mx" => {
+                let (domain_spec, cidr_start) = self.optional_domain_spec(rest, rest_start)?;
+                let (ip4_subnet_mask, ip6_subnet_mask) =
+                    self.dual_cidr_length(cidr_start, span.end)?;
+                Mechanism::Mx(MxMechanism {
+                    raw_value,
+                    domain_spec,
+                    domain_name: None,
+                    hosts: vec![],
+                    ip4_subnet_mask,
+                    ip6_subnet_mask,
+                })
+            }
+            "ptr" => {
+                let (domain_spec, end) = self.optional_domain_spec(rest, rest_start)?;
+                if end != span.end {
+                    return Err(self.error(
+                        end..span.end,
+                        "Invalid 'ptr' mechanism",
+                        "'ptr' does not take a CIDR length",
+                        "Remove the CIDR length.",
+                    ));
+                }
+                Mechanism::Ptr(PtrMechanism {
+                    raw_value,
+                    domain_spec,
+                    domain_name: None,
+                    validated_names: None,
+                })
+            }
+            "ip4" => {
+                let (ip_address, subnet_mask) = self.ip_network(name, rest, rest_start, 32)?;
+                Mechanism::Ip4(Ip4Mechanism {
+                    raw_value,
+                    ip_address,
+                    subnet_mask,
+                })
+            }
+            "ip6" => {
+                let (ip_address, subnet_mask) = self.ip_network(name, rest, rest_start, 128)?;
+                Mechanism::Ip6(Ip6Mechanism {
+                    raw_value,
+                    ip_address,
+                    subnet_mask,
+                })
+            }
+            "exists" => Mechanism::Exists(ExistsMechanism {
+                raw_value,
+                domain_spec: self.required_domain_spec(name, rest, rest_start)?,
+                domain_name: None,
+                ip_addresses: vec![],
+            }),
+            _ => {
+                return Err(self.error(
+                    name_span,
+                    format!("Unknown mechanism '{}'", name),
+                    format!("'{}' is an unknown mechanism", name),
+                    "Use one of the mechanisms all, include, a, mx, ptr, ip4, ip6 or exists.",
+                ))
+            }
+        };
+
+        Ok(Term::Directive(Directive {
+            qualifier,
+            mechanism,
+            span,
+        }))
+    }
+
+    fn modifier(
+        &self,
+        span: Range<usize>,
+        name: &str,
+        name_span: Range<usize>,
+        value: &str,
+        value_start: usize,
+    ) -> Result<Term, Box<SyntaxError>> {
+        // name = ALPHA *( ALPHA / DIGIT / "-" / "_" / "." )
+        let is_valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !is_valid_name {
+            return Err(self.error(
+                name_span,
+                format!("Invalid modifier name '{}'", name),
+                "Invalid modifier name",
+                "Modifier names start with a letter followed by letters, digits, '-', '_' or '.'.",
+            ));
+        }
+
+        let raw_value = self.raw_rdata[span.clone()].to_string();
+        match name.to_ascii_lowercase().as_str() {
+            "redirect" => Ok(Term::Modifier(Modifier::Redirect(RedirectModifier {
+                raw_value,
+                span,
+                version: Version::from_str("").unwrap(),
+                domain_spec: self.domain_spec(name, value, value_start)?,
+                terms: vec![],
+                raw_rdata: String::new(),
+            }))),
+            "exp" => Ok(Term::Modifier(Modifier::Exp(ExpModifier {
+                raw_value,
+                span,
+                domain_spec: self.domain_spec(name, value, value_start)?,
+                domain_name: None,
+                explain_string: None,
+                explanation: None,
+            }))),
+            // Unknown modifiers are ignored, but their value must be a valid macro-string
+            _ => {
+                MacroString::parse(value).map_err(|err| self.rebase(*err, value_start))?;
+                Ok(Term::Unknown(UnknownTerm {
+                    raw_rdata: raw_value,
+                    span,
+                    reason: None,
+                    lookup_error: None,
+                    lookup_limit_exceeded: false,
+                    error: None,
+                }))
+            }
+        }
+    }
+
+    /// `":" domain-spec`
+    fn required_domain_spec(
+        &self,
+        name: &str,
+        rest: &str,
+        rest_start: usize,
+    ) -> Result<String, Box<SyntaxError>> {
+        let Some(domain_spec) = rest.strip_prefix(':') else {
+            return Err(self.error(
+                rest_start..rest_start + rest.len(),
+                format!("'{}' requires a domain-spec", name),
+                "Domain name is missing",
+                format!("Add the domain name after '{}:'.", name),
+            ));
+        };
+
+        self.domain_spec(name, domain_spec, rest_start + 1)
+    }
+
+    /// `[ ":" domain-spec ]` returning the position after the domain-spec
+    fn optional_domain_spec(
+        &self,
+        rest: &str,
+        rest_start: usize,
+    ) -> Result<(Option<String>, usize), Box<SyntaxError>> {
+        let Some(value) = rest.strip_prefix(':') else {
+            return Ok((None, rest_start));
+        };
+
+        let value_start = rest_start + 1;
+        let length = cidr_position(value).unwrap_or(value.len());
+        let domain_spec = self.domain_spec("", &value[..length], value_start)?;
+
+        Ok((Some(domain_spec), value_start + length))
+    }
+
+    /// `domain-spec = macro-string domain-end`
+    fn domain_spec(
+        &self,
+        name: &str,
+        domain_spec: &str,
+        start: usize,
+    ) -> Result<String, Box<SyntaxError>> {
+        let span = start..start + domain_spec.len();
+        if domain_spec.is_empty() {
+            let help = if name.is_empty() {
+                "Add the domain name or remove the ':'.".to_string()
+            } else {
+                format!("Add the domain name to '{}'.", name)
+            };
+            return Err(self.error(
+                span.start.saturating_sub(1)..span.end,
+                "Domain name is missing",
+                "Domain name is missing",
+                help,
+            ));
+        }
+
+        MacroString::parse(domain_spec).map_err(|err| self.rebase(*err, start))?;
+
+        if !has_domain_end(domain_spec) {
+            return Err(self.error(
+                span,
+                format!("Invalid domain name '{}'", domain_spec),
+                "Domain name must end with a top-level domain or a macro",
+                "Use a fully qualified domain name (e.g. 'example.com').",
+            ));
+        }
+
+        Ok(domain_spec.to_string())
+    }
+
+    /// `dual-cidr-length = [ ip4-cidr-length ] [ "/" ip6-cidr-length ]`
+    fn dual_cidr_length(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<(Option<u8>, Option<u8>), Box<SyntaxError>> {
+        let value = &self.raw_rdata[start..end];
+        if value.is_empty() {
+            return Ok((None, None));
+        }
+        let Some(value) = value.strip_prefix('/') else {
+            return Err(self.error(
+                start..end,
+                "Invalid CIDR length",
+                "Expected '/' followed by the CIDR length",
+                "Use a CIDR length like '/24' or '//64'.",
+            ));
+        };
+
+        let (ip4, ip6) = match value.split_once("//") {
+            Some((ip4, ip6)) => (ip4, Some(ip6)),
+            None => match value.strip_prefix('/') {
+                Some(ip6) => ("", Some(ip6)),
+                None => (value, None),
+            },
+        };
+
+        let ip4_start = start + 1;
+        let ip4_length = match ip4 {
+            "" => None,
+            ip4 => Some(self.cidr_length(ip4, ip4_start, 32)?),
+        };
+        let ip6_length = match ip6 {
+            None => None,
+            Some(ip6) => {
+                let ip6_start = end - ip6.len();
+                Some(self.cidr_length(ip6, ip6_start, 128)?)
+            }
+        };
+
+        Ok((ip4_length, ip6_length))
+    }
+
+    /// `":" ip-network [ "/" cidr-length ]`
+    fn ip_network<T: FromStr>(
+        &self,
+        name: &str,
+        rest: &str,
+        rest_start: usize,
+        max_length: u8,
+    ) -> Result<(T, Option<u8>), Box<SyntaxError>> {
+        let address_type = if max_length == 32 { "IPv4" } else { "IPv6" };
+        let Some(value) = rest.strip_prefix(':') else {
+            return Err(self.error(
+                rest_start..rest_start + rest.len(),
+                format!("'{}' requires an {} address", name, address_type),
+                format!("{} address is missing", address_type),
+                format!("Add the {} address after '{}:'.", address_type, name),
+            ));
+        };
+
+        let value_start = rest_start + 1;
+        let (address, cidr) = match value.split_once('/') {
+            Some((address, cidr)) => (address, Some(cidr)),
+            None => (value, None),
+        };
+
+        let Ok(ip_address) = T::from_str(address) else {
+            return Err(self.error(
+                value_start..value_start + address.len(),
+                format!("Invalid {} address '{}'", address_type, address),
+                format!("'{}' is not a valid {} address", address, address_type),
+                format!("Use a valid {} address after '{}:'.", address_type, name),
+            ));
+        };
+
+        let subnet_mask = match cidr {
+            None => None,
+            Some(cidr) => {
+                let cidr_start = value_start + address.len() + 1;
+                Some(self.cidr_length(cidr, cidr_start, max_length)?)
+            }
+        };
+
+        Ok((ip_address, subnet_mask))
+    }
+
+    fn cidr_length(
+        &self,
+        value: &str,
+        start: usize,
+        max_length: u8,
+    ) -> Result<u8, Box<SyntaxError>> {
+        let span = start.saturating_sub(1)..start + value.len();
+        let is_digits = !value.is_empty() && value.chars().all(|c| c.is_ascii_digit());
+
+        match value.parse::<u8>() {
+            Ok(length) if is_digits && length <= max_length => Ok(length),
+            _ => Err(self.error(
+                span,
+                format!("Invalid CIDR length '{}'", value),
+                format!("CIDR length must be between 0 and {}", max_length),
+                format!("Use a CIDR length between 0 and {}.", max_length),
+            )),
+        }
+    }
+
+    /// Shift the spans of an error of a part to the position in the raw record
+    fn rebase(&self, err: SyntaxError, offset: usize) -> Box<SyntaxError> {
+        let labels = err
+            .src_labels
+            .iter()
+            .flatten()
+            .map(|label| {
+                LabelSpan::new(
+                    label.label().map(str::to_owned),
+                    offset + label.offset(),
+                    label.len(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Box::new(SyntaxError {
+            src: Some(self.raw_rdata.to_string()),
+            src_labels: Some(labels),
+            ..err
+        })
+    }
+
+    fn error(
+        &self,
+        span: Range<usize>,
+        message: impl Into<String>,
+        label: impl Into<String>,
+        help: impl Into<String>,
+    ) -> Box<SyntaxError> {
+        Box::new(
+            SyntaxError::new(message)
+                .with_src(self.raw_rdata)
+                .with_src_labels(vec![LabelSpan::at(span, label.into())])
+                .with_help(help),
+        )
+    }
+}
+
+/// The position of the CIDR length after a domain-spec, ignoring delimiters inside macros
+fn cidr_position(value: &str) -> Option<usize> {
+    let mut in_macro = false;
+    let mut previous = None;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            '{' if previous == Some('%') => in_macro = true,
+            '}' => in_macro = false,
+            '/' if !in_macro => return Some(index),
+            _ => {}
+        }
+        previous = Some(c);
+    }
+
+    None
+}
+
+/// `domain-end = ( "." toplabel [ "." ] ) / macro-expand`
+fn has_domain_end(domain_spec: &str) -> bool {
+    let domain_spec = domain_spec.strip_suffix('.').unwrap_or(domain_spec);
+    if domain_spec.ends_with('}') {
+        return true;
+    }
+
+    let Some((_, toplabel)) = domain_spec.rsplit_once('.') else {
+        return false;
+    };
+
+    // toplabel = ( *alphanum ALPHA *alphanum ) / ( 1*alphanum "-" *( alphanum / "-" ) alphanum )
+    let is_alphanum = toplabel.chars().all(|c| c.is_ascii_alphanumeric());
+    let has_alpha = toplabel.chars().any(|c| c.is_ascii_alphabetic());
+    let is_hyphenated = toplabel.contains('-')
+        && toplabel
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !toplabel.starts_with('-')
+        && !toplabel.ends_with('-');
+
+    !toplabel.is_empty() && ((is_alphanum && has_alpha) || is_hyphenated)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_single(raw_rdata: &str) -> Term {
+        let (_, mut terms) = parse_record(raw_rdata);
+        assert_eq!(terms.len(), 1, "expected exactly one term");
+        terms.remove(0)
+    }
+
+    fn error_label(term: Term) -> (usize, usize) {
+        let Term::Unknown(UnknownTerm {
+            error: Some(err), ..
+        }) = term
+        else {
+            panic!("expected a syntax error");
+        };
+        let label = err.src_labels.unwrap().pop().unwrap();
+        (label.offset(), label.len())
+    }
+
+    #[test]
+    fn it_should_parse_version_and_terms_with_spans() {
+        let (version, terms) = parse_record("v=spf1  a   -all ");
+
+        assert_eq!(version.version, "v=spf1");
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].span(), 8..9);
+        assert_eq!(terms[1].span(), 12..16);
+    }
+
+    #[test]
+    fn it_should_parse_mechanisms_case_insensitive() {
+        let term = parse_single("v=spf1 INCLUDE:_spf.example.com");
+
+        assert!(matches!(
+            term,
+            Term::Directive(d) if matches!(&d.mechanism, Mechanism::Include(i) if i.domain_spec == "_spf.example.com")
+        ));
+    }
+
+    #[test]
+    fn it_should_parse_qualifiers() {
+        let (_, terms) = parse_record("v=spf1 +a -mx ~ptr ?all");
+
+        let qualifiers = terms
+            .iter()
+            .map(|term| match term {
+                Term::Directive(d) => d.qualifier.as_ref().map(|q| q.as_str()),
+                _ => panic!("expected a directive"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(qualifiers, vec![Some("+"), Some("-"), Some("~"), Some("?")]);
+    }
+
+    #[test]
+    fn it_should_parse_a_with_dual_cidr_lengths() {
+        for (raw_rdata, domain_spec, ip4_subnet_mask, ip6_subnet_mask) in [
+            ("v=spf1 a", None, None, None),
+            ("v=spf1 a/24", None, Some(24), None),
+            ("v=spf1 a//64", None, None, Some(64)),
+            ("v=spf1 a/0//0", None, Some(0), Some(0)),
+            (
+                "v=spf1 a:mail.example.com/24//64",
+                Some("mail.example.com"),
+                Some(24),
+                Some(64),
+            ),
+            (
+                "v=spf1 a:%{l/}.example.com/24",
+                Some("%{l/}.example.com"),
+                Some(24),
+                None,
+            ),
+        ] {
+            let term = parse_single(raw_rdata);
+
+            assert!(
+                matches!(
+                    &term,
+                    Term::Directive(d) if matches!(&d.mechanism, Mechanism::A(a)
+                        if a.domain_spec.as_deref() == domain_spec
+                            && a.ip4_subnet_mask == ip4_subnet_mask
+                            && a.ip6_subnet_mask == ip6_subnet_mask)
+                ),
+                "{}",
+                raw_rdata
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_parse_mx_with_dual_cidr_lengths() {
+        let term = parse_single("v=spf1 mx:example.com/30//126");
+
+        assert!(matches!(
+            &term,
+            Term::Directive(d) if matches!(&d.mechanism, Mechanism::Mx(mx)
+                if mx.ip4_subnet_mask == Some(30) && mx.ip6_subnet_mask == Some(126))
+        ));
+    }
+
+    #[test]
+    fn it_should_report_invalid_dual_cidr_lengths() {
+        assert_eq!(error_label(parse_single("v=spf1 a/33")), (8, 3));
+        assert_eq!(error_label(parse_single("v=spf1 mx//129")), (10, 4));
+        assert_eq!(error_label(parse_single("v=spf1 a/24/64")), (8, 6));
+        assert_eq!(error_label(parse_single("v=spf1 mx/24//")), (13, 1));
+    }
+
+    #[test]
+    fn it_should_parse_ip_networks() {
+        let (_, terms) = parse_record("v=spf1 ip4:192.0.2.0/24 ip6:2001:db8::/32");
+
+        assert!(matches!(
+            &terms[0],
+            Term::Directive(d) if matches!(&d.mechanism, Mechanism::Ip4(m) if m.subnet_mask == Some(24))
+        ));
+        assert!(matches!(
+            &terms[1],
+            Term::Directive(d) if matches!(&d.mechanism, Mechanism::Ip6(m) if m.subnet_mask == Some(32))
+        ));
+    }
+
+    #[test]
+    fn it_should_parse_modifiers() {
+        let (_, terms) = parse_record("v=spf1 redirect=_spf.example.com exp=explain.%{d} foo=bar");
+
+        assert!(matches!(&terms[0], Term::Modifier(Modifier::Redirect(_))));
+        assert!(matches!(&terms[1], Term::Modifier(Modifier::Exp(_))));
+        assert!(matches!(&terms[2], Term::Unknown(u) if u.error.is_none()));
+    }
+
+    #[test]
+    fn it_should_report_invalid_ip4_address_without_panicking() {
+        let term = parse_single("v=spf1 ip4:192.0.2.300/24");
+
+        assert_eq!(error_label(term), (11, 11));
+    }
+
+    #[test]
+    fn it_should_report_invalid_cidr_length() {
+        assert_eq!(
+            error_label(parse_single("v=spf1 ip4:192.0.2.0/33")),
+            (20, 3)
+        );
+        assert_eq!(
+            error_label(parse_single("v=spf1 ip6:2001:db8::/129")),
+            (21, 4)
+        );
+        assert_eq!(error_label(parse_single("v=spf1 a/24//129")), (12, 4));
+    }
+
+    #[test]
+    fn it_should_report_unknown_mechanism() {
+        assert_eq!(error_label(parse_single("v=spf1 -foo:example.com")), (8, 3));
+    }
+
+    #[test]
+    fn it_should_report_missing_domain_spec() {
+        assert_eq!(error_label(parse_single("v=spf1 include")), (14, 0));
+        assert_eq!(error_label(parse_single("v=spf1 exists:")), (13, 1));
+    }
+
+    #[test]
+    fn it_should_report_invalid_macro_with_span() {
+        assert_eq!(
+            error_label(parse_single("v=spf1 include:%{x}.example.com")),
+            (17, 1)
+        );
+    }
+
+    #[test]
+    fn it_should_report_invalid_domain_end() {
+        assert_eq!(error_label(parse_single("v=spf1 a:localhost")), (9, 9));
+        assert!(matches!(
+            parse_single("v=spf1 a:example.com."),
+            Term::Directive(_)
+        ));
+    }
+
+    #[test]
+    fn it_should_report_modifier_with_qualifier() {
+        assert_eq!(
+            error_label(parse_single("v=spf1 -redirect=example.com")),
+            (7, 1)
+        );
+    }
+
+    #[test]
+    fn it_should_report_all_with_arguments() {
+        assert_eq!(
+            error_label(parse_single("v=spf1 all:example.com")),
+            (10, 12)
+        );
+    }
+
+    #[test]
+    fn it_should_not_panic_on_arbitrary_input() {
+        for raw_rdata in [
+            "",
+            " ",
+            "v=spf1 ",
+            "v=spf1 :",
+            "v=spf1 /",
+            "v=spf1 =",
+            "v=spf1 +",
+            "v=spf1 a:/",
+            "v=spf1 a///",
+            "v=spf1 ip4:",
+            "v=spf1 ip6:::1/",
+            "v=spf1 mx:%{",
+            "v=spf1 exists:%{d}/24",
+            "v=spf1 ä:ö.ü",
+        ] {
+            parse_record(raw_rdata);
+        }
+    }
+}
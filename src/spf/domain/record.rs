@@ -0,0 +1,36 @@
+use crate::spf::domain::parser::parse_record;
+use crate::spf::domain::{Term, Version};
+
+/// A parsed SPF record
+///
+/// Parsing does not perform any DNS lookups, so the terms of a parsed record are unresolved
+/// (e.g. includes have no nested terms) until it is passed through the resolver.
+pub struct SpfRecord {
+    /// The version of the SPF record (e.g. "spf1")
+    pub version: Version,
+
+    /// The list of directives or modifiers
+    pub terms: Vec<Term>,
+
+    /// RDATA of a single DNS TXT resource record
+    pub raw_rdata: String,
+}
+
+impl SpfRecord {
+    /// Returns true if the text starts with the version section "v=spf1" (RFC 7208 section 4.5)
+    pub fn is_spf_record(text: &str) -> bool {
+        let version = text.split(' ').next().unwrap_or_default();
+        version.eq_ignore_ascii_case("v=spf1")
+    }
+
+    /// Parse the raw record without any network access
+    pub fn parse(raw_rdata: &str) -> Self {
+        let (version, terms) = parse_record(raw_rdata);
+
+        SpfRecord {
+            version,
+            terms,
+            raw_rdata: raw_rdata.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt::{Display, Formatter};
+
+use crate::spf::domain::QualifierType;
+
+/// The result of an SPF evaluation (RFC 7208 section 2.6)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpfResult {
+    /// No syntactically valid DNS domain name was extracted or no SPF record was found.
+    None,
+    /// The domain owner has explicitly stated that it is not asserting whether the IP address is authorized.
+    Neutral,
+    /// The client is authorized to inject mail with the given identity.
+    Pass,
+    /// The client is not authorized to use the domain in the given identity.
+    Fail,
+    /// The host is probably not authorized, but the domain owner is not willing to make a strong policy statement.
+    SoftFail,
+    /// A transient (generally DNS) error occurred while performing the check.
+    TempError,
+    /// The published records could not be correctly interpreted.
+    PermError,
+}
+
+impl SpfResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpfResult::None => "none",
+            SpfResult::Neutral => "neutral",
+            SpfResult::Pass => "pass",
+            SpfResult::Fail => "fail",
+            SpfResult::SoftFail => "softfail",
+            SpfResult::TempError => "temperror",
+            SpfResult::PermError => "permerror",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SpfResult::None => "No SPF record was found for the domain.",
+            SpfResult::Neutral => {
+                "The domain owner does not assert whether the IP address is authorized."
+            }
+            SpfResult::Pass => "The IP address is authorized to send mail for the domain.",
+            SpfResult::Fail => "The IP address is not authorized to send mail for the domain.",
+            SpfResult::SoftFail => {
+                "The IP address is probably not authorized to send mail for the domain."
+            }
+            SpfResult::TempError => "A transient DNS error occurred while performing the check.",
+            SpfResult::PermError => "The published SPF records could not be correctly interpreted.",
+        }
+    }
+}
+
+impl From<&QualifierType> for SpfResult {
+    fn from(qualifier: &QualifierType) -> Self {
+        match qualifier {
+            QualifierType::Pass => SpfResult::Pass,
+            QualifierType::Fail => SpfResult::Fail,
+            QualifierType::SoftFail => SpfResult::SoftFail,
+            QualifierType::Neutral => SpfResult::Neutral,
+        }
+    }
+}
+
+impl Display for SpfResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -1,58 +1,79 @@
+use crate::dns::domain::DnsError;
 use crate::spf::domain::directive::Directive;
-use crate::spf::domain::mechanism::Mechanism;
 use crate::spf::domain::modifier::Modifier;
-use crate::spf::domain::qualifier::QualifierType;
+use crate::spf::domain::parser;
+use crate::spf::domain::SyntaxError;
+use std::ops::Range;
 use std::str::FromStr;
 
 pub enum Term {
     Directive(Directive),
     Modifier(Modifier),
     Unknown(UnknownTerm),
+    Loop(LoopTerm),
 }
 
 impl Term {
-    pub fn new_unknown(raw_rdata: impl Into<String>, reason: Option<String>) -> Self {
+    pub fn new_unknown(
+        raw_rdata: impl Into<String>,
+        span: Range<usize>,
+        reason: Option<String>,
+    ) -> Self {
         Self::Unknown(UnknownTerm {
             raw_rdata: raw_rdata.into(),
+            span,
             reason,
+            lookup_error: None,
+            lookup_limit_exceeded: false,
+            error: None,
         })
     }
+
+    /// The byte range of the term in the raw record
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Term::Directive(d) => d.span.clone(),
+            Term::Modifier(m) => m.span(),
+            Term::Unknown(u) => u.span.clone(),
+            Term::Loop(l) => l.span.clone(),
+        }
+    }
 }
 
 impl FromStr for Term {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (qualifier, mechanism_str) = if s.starts_with(QualifierType::Pass.as_str()) {
-            (QualifierType::Pass.as_str(), &s[1..])
-        } else if s.starts_with(QualifierType::Fail.as_str()) {
-            (QualifierType::Fail.as_str(), &s[1..])
-        } else if s.starts_with(QualifierType::SoftFail.as_str()) {
-            (QualifierType::SoftFail.as_str(), &s[1..])
-        } else if s.starts_with(QualifierType::Neutral.as_str()) {
-            (QualifierType::Neutral.as_str(), &s[1..])
-        } else {
-            ("", s)
-        };
-        let qualifier = match QualifierType::from_str(qualifier) {
-            Ok(q) => Some(q),
-            Err(_) => None,
-        };
-
-        match Mechanism::from_str(mechanism_str) {
-            Ok(mechanism) => Ok(Term::Directive(Directive {
-                qualifier,
-                mechanism,
-            })),
-            Err(_) => Ok(Term::Unknown(UnknownTerm {
-                raw_rdata: mechanism_str.to_string(),
-                reason: None,
-            })),
-        }
+        Ok(parser::parse_term(s, 0..s.len()))
     }
 }
 
 pub struct UnknownTerm {
     pub raw_rdata: String,
+
+    /// The byte range of the term in the raw record
+    pub span: Range<usize>,
+
+    /// Why the term could not be resolved (e.g. the included domain has no SPF record)
     pub reason: Option<String>,
+
+    /// The DNS error that prevented resolving the term
+    pub lookup_error: Option<DnsError>,
+
+    /// The term was not resolved as the lookup limit was reached before it
+    pub lookup_limit_exceeded: bool,
+
+    /// The syntax error if the term does not follow the SPF grammar
+    pub error: Option<SyntaxError>,
+}
+
+/// An include or redirect whose target is already being resolved
+pub struct LoopTerm {
+    pub raw_rdata: String,
+
+    /// The byte range of the term in the raw record
+    pub span: Range<usize>,
+
+    /// The domain names from the checked domain up to the repeated one
+    pub chain: Vec<String>,
 }
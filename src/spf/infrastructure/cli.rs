@@ -1,13 +1,23 @@
 use std::error::Error;
+use std::net::IpAddr;
 
+use async_trait::async_trait;
 use clap::Args;
 
+use crate::spf::core::evaluate::{
+    EvaluateSpfQuery, EvaluateSpfTerminalPresenter, EvaluateSpfUseCase, EvaluateSpfUseCaseImpl,
+};
 use crate::spf::domain::SpfError;
 use crate::{
-    common::{cli::CliCommand, presenter::Presenter},
-    dns::infrastructure::dns_resolver::DomainDnsResolver,
+    common::{
+        cli::{CliCommand, FailOn, OutputFormat},
+        outcome::Outcome,
+        presenter::Presenter,
+    },
+    dns::core::dns_resolver::DnsResolver,
     spf::core::check::{
-        SpfSummary, SummarySpfQuery, SummarySpfTerminalPresenter, SummarySpfUseCase,
+        SpfSummary, SummarySpfJsonPresenter, SummarySpfJunitPresenter, SummarySpfQuery,
+        SummarySpfSarifPresenter, SummarySpfTerminalPresenter, SummarySpfUseCase,
         SummarySpfUseCaseImpl, SummarySpfWithDetailTerminalPresenter,
     },
 };
@@ -23,26 +33,73 @@ pub struct Spf {
     #[arg(short, long)]
     pub record: Option<String>,
 
+    /// Only check the syntax of the record without any DNS lookups
+    #[arg(long, requires = "record", conflicts_with = "ip")]
+    pub offline: bool,
+
+    /// Evaluate whether the IP address is authorized to send mail for the domain
+    #[arg(long)]
+    pub ip: Option<IpAddr>,
+
+    /// Sender address (MAIL FROM) used for the evaluation
+    /// (defaults to postmaster@<domain>)
+    #[arg(long, requires = "ip")]
+    pub sender: Option<String>,
+
+    /// Domain name of the HELO/EHLO command used for the evaluation
+    #[arg(long, requires = "ip")]
+    pub helo: Option<String>,
+
+    /// Output format of the check
+    #[arg(long, value_enum, default_value_t, conflicts_with = "ip")]
+    pub format: OutputFormat,
+
+    /// Lowest severity of the findings that makes the check exit unsuccessfully
+    /// (a softfail of `--ip` is a warning)
+    #[arg(long, value_enum, default_value_t)]
+    pub fail_on: FailOn,
+
     /// Domain name to check
     pub domain: String,
 }
 
+#[async_trait(?Send)]
 impl CliCommand<Spf> for Spf {
-    fn execute(&self) -> Result<(), Box<dyn Error>> {
-        let mut dns_resolver_gateway = DomainDnsResolver::new();
-        let presenter: Box<dyn Presenter<SpfSummary, SpfError>> = if self.detail {
-            Box::new(SummarySpfWithDetailTerminalPresenter::new())
-        } else {
-            Box::new(SummarySpfTerminalPresenter::new())
+    async fn execute(&self, dns_resolver: &dyn DnsResolver) -> Result<Outcome, Box<dyn Error>> {
+        if let Some(ip_address) = self.ip {
+            let presenter = Box::new(EvaluateSpfTerminalPresenter::new());
+            let mut evaluate_spf_use_case = EvaluateSpfUseCaseImpl::new(dns_resolver);
+
+            let query = EvaluateSpfQuery {
+                domain_name: self.domain.to_owned(),
+                ip_address,
+                sender: self.sender.to_owned(),
+                helo: self.helo.to_owned(),
+                record: self.record.to_owned(),
+            };
+            let outcome = evaluate_spf_use_case.execute(&query, presenter).await;
+
+            return Ok(self.fail_on.apply(outcome));
+        }
+
+        let presenter: Box<dyn Presenter<SpfSummary, SpfError>> = match self.format {
+            OutputFormat::Json => Box::new(SummarySpfJsonPresenter::new(&self.domain)),
+            OutputFormat::Sarif => Box::new(SummarySpfSarifPresenter::new(&self.domain)),
+            OutputFormat::Junit => Box::new(SummarySpfJunitPresenter::new(&self.domain)),
+            OutputFormat::Text if self.detail => {
+                Box::new(SummarySpfWithDetailTerminalPresenter::new())
+            }
+            OutputFormat::Text => Box::new(SummarySpfTerminalPresenter::new()),
         };
-        let mut summary_spf_use_case = SummarySpfUseCaseImpl::new(&mut dns_resolver_gateway);
+        let mut summary_spf_use_case = SummarySpfUseCaseImpl::new(dns_resolver);
 
         let query = SummarySpfQuery {
             domain_name: self.domain.to_owned(),
             record: self.record.to_owned(),
+            offline: self.offline,
         };
-        summary_spf_use_case.execute(&query, presenter);
+        let outcome = summary_spf_use_case.execute(&query, presenter).await;
 
-        Ok(())
+        Ok(self.fail_on.apply(outcome))
     }
 }